@@ -0,0 +1,97 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use polygonal_puzzle::{
+  polygon_matcher::{self, options::MatchOptions},
+  shapes::{point::Point, polygon::Polygon},
+};
+
+static INPUT_DATA: &str = include_str!("../tests/data/input");
+
+/// Pairs of polygons of the official data, each one given by its number of vertices
+/// followed by one line per vertex.
+fn cases() -> Vec<(Polygon, Polygon)> {
+  let lines = &mut INPUT_DATA.lines();
+  let mut polygons = vec![];
+
+  while let Some(line) = lines.next() {
+    let n = line.parse().expect("should parse an integer");
+    polygons.push(Polygon::from(n, lines).expect("should parse a polygon"));
+  }
+
+  polygons
+    .chunks_exact(2)
+    .map(|pair| (pair[0].clone(), pair[1].clone()))
+    .collect()
+}
+
+fn official_data(c: &mut Criterion) {
+  let cases = cases();
+  let mut group = c.benchmark_group("official_data");
+  group.sample_size(10);
+
+  group.bench_function("best_match", |b| {
+    b.iter(|| {
+      cases
+        .iter()
+        .map(|(p1, p2)| polygon_matcher::best_match(p1, p2).unwrap().boundary)
+        .sum::<f64>()
+    });
+  });
+
+  group.finish();
+}
+
+/// Two pieces cut apart along a jagged line of `n` vertices, one below it and one above
+/// it, whose longest common boundary is that line.
+fn cut_pieces(n: usize) -> (Polygon, Polygon) {
+  let cut: Vec<Point> = (0..n)
+    .map(|i| Point {
+      x: (3 * i) as f64,
+      y: ((37 * i) % 11) as f64 * 0.7 + ((13 * i) % 5) as f64 * 0.3,
+    })
+    .collect();
+  let end = cut[n - 1].x;
+
+  let mut below = vec![
+    Point {
+      x: 0_f64,
+      y: -20_f64,
+    },
+    Point { x: end, y: -20_f64 },
+  ];
+  below.extend(cut.iter().rev());
+  let mut above = cut;
+  above.extend([
+    Point { x: end, y: 30_f64 },
+    Point {
+      x: 0_f64,
+      y: 30_f64,
+    },
+  ]);
+
+  (Polygon::new(below), Polygon::new(above))
+}
+
+/// The pruned search against solving every pair, on pieces with a long common boundary
+/// that most pairs cannot reach.
+fn cut_line(c: &mut Criterion) {
+  let (below, above) = cut_pieces(100);
+  let options = MatchOptions::default();
+  let mut group = c.benchmark_group("cut_line");
+  group.sample_size(10);
+
+  group.bench_function("pruned", |b| {
+    b.iter(|| {
+      polygon_matcher::best_match_with(&below, &above, &options)
+        .unwrap()
+        .boundary
+    });
+  });
+  group.bench_function("every_pair", |b| {
+    b.iter(|| polygon_matcher::best_matches_with(&below, &above, 1, &options).unwrap()[0].boundary);
+  });
+
+  group.finish();
+}
+
+criterion_group!(benches, official_data, cut_line);
+criterion_main!(benches);
@@ -0,0 +1,281 @@
+use std::io;
+
+use polygonal_puzzle::{error::PuzzleError, shapes::polygon::Polygon, tolerance::Tolerance};
+
+use crate::cli::Validation;
+
+/// The polygon as written, as `Polygon::from` stores the vertices in reverse.
+fn written(polygon: &Polygon) -> Polygon {
+  Polygon::new(polygon.vertices.iter().rev().copied().collect())
+}
+
+/// Error listing the defects of `polygon`, described by `which`.
+fn defects(polygon: &Polygon, which: &str, tol: &Tolerance) -> PuzzleError {
+  let defects: Vec<String> = written(polygon)
+    .validate(tol)
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+
+  PuzzleError::DegeneratePolygon(format!("{which}: {}", defects.join("; ")))
+}
+
+/// Vertices of `polygon` that `repaired` lacks, numbered from 0 in input order.
+fn removed(polygon: &Polygon, repaired: &Polygon) -> Vec<usize> {
+  let mut kept = repaired.vertices.iter().peekable();
+  let mut removed: Vec<usize> = polygon
+    .vertices
+    .iter()
+    .enumerate()
+    .filter(|(_, v)| kept.next_if_eq(v).is_none())
+    .map(|(i, _)| polygon.len() - 1 - i)
+    .collect();
+
+  removed.sort_unstable();
+  removed
+}
+
+/// The vertices removed from a polygon, as in "vertices 1, 4".
+fn vertices(removed: &[usize]) -> String {
+  let numbers: Vec<String> = removed.iter().map(ToString::to_string).collect();
+  let noun = if removed.len() == 1 {
+    "vertex"
+  } else {
+    "vertices"
+  };
+
+  format!("{noun} {}", numbers.join(", "))
+}
+
+/// The polygon described by `which` repaired, adding the vertices removed to `repairs`.
+/// A polygon still invalid once repaired gives the vertices removed and the defects
+/// left, numbered along the vertices kept.
+fn repair(
+  polygon: &Polygon,
+  which: &str,
+  tol: &Tolerance,
+  repairs: &mut Vec<String>,
+) -> Result<Polygon, PuzzleError> {
+  let repaired = polygon.repaired(tol);
+  let removed = removed(polygon, &repaired);
+
+  if !repaired.validate(tol).is_empty() {
+    let which = if removed.is_empty() {
+      which.to_owned()
+    } else {
+      format!("{which} without {}", vertices(&removed))
+    };
+    return Err(defects(&repaired, &which, tol));
+  }
+  if !removed.is_empty() {
+    repairs.push(format!("{which}: removed {}", vertices(&removed)));
+  }
+
+  Ok(repaired)
+}
+
+/// A case read from the input: both polygons, or why the case was rejected, and what
+/// was repaired in them.
+pub struct Case {
+  pub polygons: Result<(Polygon, Polygon), PuzzleError>,
+  /// One line for each polygon that was repaired.
+  pub repairs: Vec<String>,
+}
+
+/// Cases read from the lines of the input, up to the first error. Errors give the line
+/// they were found on. A case with a polygon that fails validation is still read in
+/// full, so that the next case can follow it.
+pub struct CaseReader<I> {
+  lines: I,
+  /// Lines read so far.
+  line: usize,
+  validation: Option<Validation>,
+}
+
+impl<I: Iterator<Item = io::Result<String>>> CaseReader<I> {
+  pub const fn new(lines: I, validation: Option<Validation>) -> Self {
+    Self {
+      lines,
+      line: 0,
+      validation,
+    }
+  }
+
+  fn next_line(&mut self) -> Result<Option<String>, PuzzleError> {
+    let line = self.lines.next().transpose()?;
+    self.line += usize::from(line.is_some());
+    Ok(line)
+  }
+
+  /// Polygon whose vertex count is `count_line`, the line just read, and the number of
+  /// that line.
+  fn polygon(&mut self, count_line: &str) -> Result<(Polygon, usize), PuzzleError> {
+    let n: usize = count_line.parse().map_err(|e| {
+      PuzzleError::parse(
+        self.line,
+        1,
+        format!("invalid vertex count {count_line:?}: {e}"),
+      )
+    })?;
+
+    let start = self.line;
+    let lines = (0..n)
+      .map_while(|_| self.next_line().transpose())
+      .collect::<Result<Vec<String>, _>>()?;
+
+    let polygon =
+      Polygon::from(n, &mut lines.iter().map(String::as_str)).map_err(|e| e.below(start))?;
+    Ok((polygon, start))
+  }
+
+  /// The polygon whose vertex count is on `line`, checked and repaired as asked, with
+  /// vertices numbered from 0 in input order.
+  fn checked(
+    &self,
+    (polygon, line): (Polygon, usize),
+    repairs: &mut Vec<String>,
+  ) -> Result<Polygon, PuzzleError> {
+    let Some(validation) = self.validation else {
+      return Ok(polygon);
+    };
+
+    let tol = Tolerance::from_polygons(&[&polygon]);
+    let which = format!("polygon on line {line}");
+    match validation {
+      Validation::Reject if polygon.validate(&tol).is_empty() => Ok(polygon),
+      Validation::Reject => Err(defects(&polygon, &which, &tol)),
+      Validation::Repair => repair(&polygon, &which, &tol, repairs),
+    }
+  }
+
+  fn case(&mut self, count_line: &str) -> Result<Case, PuzzleError> {
+    let first = self.polygon(count_line)?;
+    let count_line = self
+      .next_line()?
+      .ok_or_else(|| PuzzleError::parse(self.line + 1, 1, "each case should have 2 polygons"))?;
+    let second = self.polygon(&count_line)?;
+
+    let mut repairs = vec![];
+    let polygons = self
+      .checked(first, &mut repairs)
+      .and_then(|p1| Ok((p1, self.checked(second, &mut repairs)?)));
+
+    Ok(Case { polygons, repairs })
+  }
+}
+
+impl<I: Iterator<Item = io::Result<String>>> Iterator for CaseReader<I> {
+  type Item = Result<Case, PuzzleError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let count_line = self.next_line().transpose()?;
+    Some(count_line.and_then(|l| self.case(&l)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_case::test_case;
+
+  fn read_with(
+    input: &str,
+    validation: Option<Validation>,
+  ) -> Vec<Result<(Polygon, Polygon), String>> {
+    CaseReader::new(input.lines().map(|l| Ok(l.to_owned())), validation)
+      .map(|case| case.and_then(|c| c.polygons).map_err(|e| e.to_string()))
+      .collect()
+  }
+
+  fn read(input: &str) -> Vec<Result<(Polygon, Polygon), String>> {
+    read_with(input, None)
+  }
+
+  #[test]
+  fn test_cases() {
+    let cases = read("3\n0 0\n1 0\n0 1\n3\n0 0\n2 0\n0 2\n3\n0 0\n1 0\n0 1\n4\n0 0\n1 0\n1 1\n0 1");
+
+    assert_eq!(cases.len(), 2);
+    assert_eq!(cases[1].as_ref().unwrap().1.len(), 4);
+  }
+
+  #[test_case(
+    "3\n0 0\n1 0\n0 1\n3\n0 0\n2 x\n0 2",
+    "line 7, column 3: invalid coordinate \"x\": invalid float literal"
+  )]
+  #[test_case(
+    "3\n0 0\n1 0\n0 1\n3\n0 0",
+    "line 7, column 1: expected 3 vertices, found 1"
+  )]
+  #[test_case(
+    "3\n0 0\n1 0\n0 1",
+    "line 5, column 1: each case should have 2 polygons"
+  )]
+  #[test_case(
+    "three\n0 0",
+    "line 1, column 1: invalid vertex count \"three\": invalid digit found in string"
+  )]
+  fn test_errors(input: &str, error: &str) {
+    assert_eq!(
+      read(input).into_iter().find_map(Result::err).unwrap(),
+      error
+    );
+  }
+
+  #[test_case(None, Ok(5))]
+  #[test_case(
+    Some(Validation::Reject),
+    Err("degenerate polygon: polygon on line 5: edge 1 has zero length")
+  )]
+  #[test_case(Some(Validation::Repair), Ok(4))]
+  fn test_validation(validation: Option<Validation>, len: Result<usize, &str>) {
+    let cases = read_with("3\n0 0\n1 0\n0 1\n5\n0 0\n2 0\n2 0\n2 2\n0 2", validation);
+
+    assert_eq!(
+      cases[0]
+        .as_ref()
+        .map(|case| case.1.len())
+        .map_err(String::as_str),
+      len
+    );
+  }
+
+  #[test]
+  fn test_rejected_case_is_skipped() {
+    let cases = read_with(
+      "3\n0 0\n2 0\n2 0\n3\n0 0\n1 0\n0 1\n3\n0 0\n1 0\n0 1\n3\n0 0\n2 0\n0 2",
+      Some(Validation::Reject),
+    );
+
+    assert_eq!(cases.len(), 2);
+    assert!(cases[0].is_err());
+    assert_eq!(cases[1].as_ref().unwrap().1.len(), 3);
+  }
+
+  #[test]
+  fn test_repairs() {
+    let input = "3\n0 0\n1 0\n0 1\n5\n0 0\n2 0\n2 0\n2 2\n0 2";
+    let case = CaseReader::new(
+      input.lines().map(|l| Ok(l.to_owned())),
+      Some(Validation::Repair),
+    )
+    .next()
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(case.repairs, ["polygon on line 5: removed vertex 1"]);
+  }
+
+  #[test]
+  fn test_unrepairable() {
+    let cases = read_with(
+      "5\n0 0\n2 0\n2 0\n0 2\n2 2\n3\n0 0\n1 0\n0 1",
+      Some(Validation::Repair),
+    );
+
+    assert_eq!(
+      cases[0].as_ref().err().unwrap(),
+      "degenerate polygon: polygon on line 1 without vertex 1: edges 1 and 3 cross at (1.000000, 1.000000)"
+    );
+  }
+}
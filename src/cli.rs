@@ -0,0 +1,139 @@
+use std::str::FromStr;
+
+pub const USAGE: &str =
+  "usage: polygonal_puzzle [--top K] [--threads N] [--stats] [--validate reject|repair] < input";
+
+pub struct Args {
+  pub top: usize,
+  /// Threads to search on, and cases to solve at once. All the cores when unset. With
+  /// a single thread, the cases are read, solved and written one at a time.
+  pub threads: Option<usize>,
+  /// Print the work done on each case to the standard error.
+  pub stats: bool,
+  /// Check each polygon before matching it. Unchecked when unset.
+  pub validation: Option<Validation>,
+}
+
+/// What to do with a polygon that is not simple.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Validation {
+  /// Skip the cases with a defect.
+  Reject,
+  /// Remove zero-length edges and spikes, and skip the cases with a defect that cannot
+  /// be removed.
+  Repair,
+}
+
+impl FromStr for Validation {
+  type Err = ();
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "reject" => Ok(Self::Reject),
+      "repair" => Ok(Self::Repair),
+      _ => Err(()),
+    }
+  }
+}
+
+impl Default for Args {
+  fn default() -> Self {
+    Self {
+      top: 1,
+      threads: None,
+      stats: false,
+      validation: None,
+    }
+  }
+}
+
+fn value<T: FromStr>(name: &str, value: Option<String>) -> Result<T, String> {
+  value
+    .and_then(|v| v.parse().ok())
+    .ok_or_else(|| format!("{name} expects a valid value"))
+}
+
+impl Args {
+  /// # Errors
+  /// Unknown arguments and invalid values.
+  pub fn parse<I>(args: I) -> Result<Self, String>
+  where
+    I: IntoIterator<Item = String>,
+  {
+    let mut parsed = Self::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+      match arg.as_str() {
+        "--top" => parsed.top = value(&arg, args.next())?,
+        "--threads" => parsed.threads = Some(value(&arg, args.next())?),
+        "--stats" => parsed.stats = true,
+        "--validate" => parsed.validation = Some(value(&arg, args.next())?),
+        _ => return Err(format!("unknown argument {arg}")),
+      }
+    }
+
+    parsed.validate()?;
+    Ok(parsed)
+  }
+
+  fn validate(&self) -> Result<(), String> {
+    if self.top == 0 {
+      return Err("--top should be at least 1".to_owned());
+    }
+
+    if self.threads == Some(0) {
+      return Err("--threads should be at least 1".to_owned());
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_case::test_case;
+
+  fn parse(args: &[&str]) -> Result<Args, String> {
+    Args::parse(args.iter().map(|a| (*a).to_owned()))
+  }
+
+  #[test_case(&[], 1)]
+  #[test_case(&["--top", "5"], 5)]
+  #[test_case(&["--top", "2", "--top", "3"], 3)]
+  fn test_parse(args: &[&str], top: usize) {
+    assert_eq!(parse(args).unwrap().top, top);
+  }
+
+  #[test_case(&[], None)]
+  #[test_case(&["--threads", "1"], Some(1))]
+  #[test_case(&["--top", "2", "--threads", "8"], Some(8))]
+  fn test_parse_threads(args: &[&str], threads: Option<usize>) {
+    assert_eq!(parse(args).unwrap().threads, threads);
+  }
+
+  #[test_case(&[], false)]
+  #[test_case(&["--stats", "--top", "2"], true)]
+  fn test_parse_stats(args: &[&str], stats: bool) {
+    assert_eq!(parse(args).unwrap().stats, stats);
+  }
+
+  #[test_case(&[], None)]
+  #[test_case(&["--validate", "reject"], Some(Validation::Reject))]
+  #[test_case(&["--validate", "repair", "--stats"], Some(Validation::Repair))]
+  fn test_parse_validation(args: &[&str], validation: Option<Validation>) {
+    assert_eq!(parse(args).unwrap().validation, validation);
+  }
+
+  #[test_case(&["--top"], "--top expects a valid value")]
+  #[test_case(&["--top", "x"], "--top expects a valid value")]
+  #[test_case(&["--top", "0"], "--top should be at least 1")]
+  #[test_case(&["--threads"], "--threads expects a valid value")]
+  #[test_case(&["--threads", "0"], "--threads should be at least 1")]
+  #[test_case(&["--validate", "fix"], "--validate expects a valid value")]
+  #[test_case(&["--bottom"], "unknown argument --bottom")]
+  fn test_parse_error(args: &[&str], error: &str) {
+    assert_eq!(parse(args).err().unwrap(), error);
+  }
+}
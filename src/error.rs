@@ -0,0 +1,80 @@
+use std::{error::Error, fmt::Display, io};
+
+/// Everything that can go wrong reading a puzzle, matching its pieces or writing the
+/// results.
+#[derive(Debug)]
+pub enum PuzzleError {
+  /// Malformed input. Lines and columns start at 1.
+  Parse {
+    line: usize,
+    column: usize,
+    message: String,
+  },
+  /// A polygon that cannot be placed, such as one with fewer than 3 vertices.
+  DegeneratePolygon(String),
+  /// Match options that allow no placement, such as an empty set of angles.
+  InvalidOptions(String),
+  /// Reading the input or writing the results failed.
+  Io(io::Error),
+  /// The search was stopped through its `CancelToken`.
+  Cancelled,
+}
+
+impl PuzzleError {
+  #[must_use]
+  pub fn parse(line: usize, column: usize, message: impl Into<String>) -> Self {
+    Self::Parse {
+      line,
+      column,
+      message: message.into(),
+    }
+  }
+
+  /// The same error, for text that starts `lines` lines further down the input.
+  #[must_use]
+  pub fn below(self, lines: usize) -> Self {
+    match self {
+      Self::Parse {
+        line,
+        column,
+        message,
+      } => Self::Parse {
+        line: line + lines,
+        column,
+        message,
+      },
+      other => other,
+    }
+  }
+}
+
+impl Display for PuzzleError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Parse {
+        line,
+        column,
+        message,
+      } => write!(f, "line {line}, column {column}: {message}"),
+      Self::DegeneratePolygon(reason) => write!(f, "degenerate polygon: {reason}"),
+      Self::InvalidOptions(reason) => write!(f, "invalid options: {reason}"),
+      Self::Io(e) => write!(f, "I/O error: {e}"),
+      Self::Cancelled => write!(f, "the search was cancelled"),
+    }
+  }
+}
+
+impl Error for PuzzleError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      Self::Io(e) => Some(e),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for PuzzleError {
+  fn from(e: io::Error) -> Self {
+    Self::Io(e)
+  }
+}
@@ -14,7 +14,7 @@ impl<'a> EdgeIterator<'a> {
   }
 }
 
-impl<'a> Iterator for EdgeIterator<'a> {
+impl Iterator for EdgeIterator<'_> {
   type Item = Segment;
 
   fn next(&mut self) -> Option<Self::Item> {
@@ -14,7 +14,7 @@ impl<'a> VertexIterator<'a> {
   }
 }
 
-impl<'a> Iterator for VertexIterator<'a> {
+impl Iterator for VertexIterator<'_> {
   type Item = (Point, Point, Point);
 
   fn next(&mut self) -> Option<Self::Item> {
@@ -2,7 +2,7 @@
 #![deny(clippy::let_underscore_must_use)]
 #![deny(clippy::integer_division)]
 #![deny(clippy::if_then_some_else_none)]
-#![deny(clippy::string_to_string)]
+#![deny(clippy::implicit_clone)]
 #![deny(clippy::str_to_string)]
 #![deny(clippy::try_err)]
 #![deny(clippy::panic)]
@@ -11,8 +11,11 @@
 #![allow(clippy::cast_possible_wrap)]
 
 mod constants;
+pub mod error;
 mod iterators;
 pub mod polygon_matcher;
+mod predicates;
 pub mod shapes;
+pub mod tolerance;
 pub mod traits;
 pub mod util;
@@ -2,7 +2,7 @@
 #![deny(clippy::let_underscore_must_use)]
 #![deny(clippy::integer_division)]
 #![deny(clippy::if_then_some_else_none)]
-#![deny(clippy::string_to_string)]
+#![deny(clippy::implicit_clone)]
 #![deny(clippy::str_to_string)]
 #![deny(clippy::try_err)]
 #![deny(clippy::panic)]
@@ -11,60 +11,186 @@
 #![allow(clippy::cast_possible_wrap)]
 #![allow(clippy::significant_drop_tightening)]
 
-use std::{error::Error, io};
+use std::{error::Error, io, num::NonZeroUsize, sync::Arc, thread};
 
+mod case_reader;
+mod cli;
+mod pipeline;
 mod result_output;
-use crossbeam::channel::{Receiver, Sender};
-use polygonal_puzzle::{polygon_matcher, shapes::polygon::Polygon};
+mod stats_table;
+use case_reader::{Case, CaseReader};
+use cli::Args;
+use polygonal_puzzle::{
+  error::PuzzleError,
+  polygon_matcher::{
+    self,
+    options::{MatchOptions, Parallelism},
+    MatchResult,
+  },
+  shapes::polygon::Polygon,
+};
+use rayon::ThreadPoolBuilder;
 use result_output::WriteResult;
+use stats_table::StatsTable;
 
-fn write_results_thread(r: &Receiver<(Polygon, Polygon, f64)>) {
-  let mut case_number = 1;
+/// Writes the placements of each case, numbering the cases in the order written, and
+/// the work done on them when asked to.
+struct ResultWriters {
+  case_number: i32,
+  writers: [Box<dyn WriteResult + Send>; 2],
+  stats: Option<StatsTable>,
+}
+
+impl ResultWriters {
+  fn new(stats: bool) -> Result<Self, PuzzleError> {
+    if stats {
+      eprintln!("{}", StatsTable::header());
+    }
+
+    Ok(Self {
+      case_number: 1,
+      writers: [
+        Box::new(result_output::desmos::OutputWriter::new()?),
+        Box::new(result_output::svg::OutputWriter {}),
+      ],
+      stats: stats.then(StatsTable::default),
+    })
+  }
+
+  fn write(&mut self, results: &[MatchResult]) -> Result<(), PuzzleError> {
+    for (rank, result) in results.iter().enumerate() {
+      for w in &mut self.writers {
+        w.write_result(self.case_number, rank + 1, result)?;
+      }
+    }
+
+    if let (Some(table), Some(result)) = (&mut self.stats, results.first()) {
+      eprintln!("{}", table.add(&result.stats));
+    }
 
-  let mut writers: [Box<dyn WriteResult>; 2] = [
-    Box::new(result_output::desmos::OutputWriter::new()),
-    Box::new(result_output::svg::OutputWriter {}),
-  ];
+    self.case_number += 1;
+    Ok(())
+  }
+
+  /// Prints the boundaries of the case and writes its placements, or reports that it
+  /// was rejected and prints an empty line in its place.
+  fn write_case(
+    &mut self,
+    solved: Result<Result<Vec<MatchResult>, PuzzleError>, PuzzleError>,
+  ) -> Result<(), PuzzleError> {
+    match solved {
+      Ok(results) => {
+        let results = results?;
+        print_boundaries(&results);
+        self.write(&results)
+      }
+      Err(reason) => {
+        eprintln!("case {} rejected: {reason}", self.case_number);
+        print_boundaries(&[]);
+        self.case_number += 1;
+        Ok(())
+      }
+    }
+  }
 
-  while let Ok((p1, p2, boundary)) = r.recv() {
-    writers
-      .iter_mut()
-      .for_each(|w| w.write_result(boundary, case_number, p1.clone(), p2.clone()));
+  fn finish(&mut self) -> Result<(), PuzzleError> {
+    if let Some(table) = &self.stats {
+      eprintln!("{}", table.total());
+    }
 
-    case_number += 1;
+    self.writers.iter_mut().try_for_each(|w| w.finish())
   }
 }
 
-fn main_thread(s: Sender<(Polygon, Polygon, f64)>) -> Result<(), Box<dyn Error>> {
-  let stdin_lines = &mut io::stdin().lines();
+fn print_boundaries(results: &[MatchResult]) {
+  let boundaries: Vec<String> = results
+    .iter()
+    .map(|r| format!("{:.12}", r.boundary))
+    .collect();
+
+  println!("{}", boundaries.join(" "));
+}
+
+fn parallelism(threads: Option<usize>) -> Result<Parallelism, Box<dyn Error>> {
+  Ok(match threads {
+    None => Parallelism::Global,
+    Some(1) => Parallelism::sequential()?,
+    Some(n) => Parallelism::Pool(Arc::new(ThreadPoolBuilder::new().num_threads(n).build()?)),
+  })
+}
 
-  while let Some(line) = stdin_lines.next() {
-    let n = line?.parse()?;
-    let polygon1 = Polygon::from(n, &mut stdin_lines.map(Result::unwrap))?;
+fn options(args: &Args) -> Result<MatchOptions, Box<dyn Error>> {
+  Ok(MatchOptions {
+    parallelism: parallelism(args.threads)?,
+    ..MatchOptions::default()
+  })
+}
 
-    let n = stdin_lines
-      .next()
-      .expect("should have 2 polygons per case")?
-      .parse()?;
-    let polygon2 = Polygon::from(n, &mut stdin_lines.map(Result::unwrap))?;
+/// The `top` best placements of the polygons, searched with the pruned search when only
+/// the best one is asked for.
+fn matches(
+  (p1, p2): (&Polygon, &Polygon),
+  top: usize,
+  options: &MatchOptions,
+) -> Result<Vec<MatchResult>, PuzzleError> {
+  if top == 1 {
+    polygon_matcher::best_match_with(p1, p2, options).map(|result| vec![result])
+  } else {
+    polygon_matcher::best_matches_with(p1, p2, top, options)
+  }
+}
 
-    let (p1, p2, boundary) = polygon_matcher::best_match(&polygon1, &polygon2);
+/// Cases solved at once. Each one also searches in parallel, but small cases cannot
+/// keep every core busy on their own.
+fn workers(threads: Option<usize>) -> usize {
+  threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, NonZeroUsize::get))
+}
 
-    s.send((p1.clone(), p2.clone(), boundary))?;
-    println!("{boundary:.12}");
+/// The search for the placements of the case, or why the case was rejected, reporting
+/// what was repaired in it.
+fn solve(
+  case: Case,
+  top: usize,
+  options: &MatchOptions,
+) -> Result<Result<Vec<MatchResult>, PuzzleError>, PuzzleError> {
+  for repair in &case.repairs {
+    eprintln!("{repair}");
   }
 
-  std::mem::drop(s);
+  case
+    .polygons
+    .map(|(p1, p2)| matches((&p1, &p2), top, options))
+}
+
+/// Solves every case read from the standard input, printing the boundaries and writing
+/// the placements of each one in input order. A rejected case is reported on the
+/// standard error, with an empty line printed in its place.
+fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+  let options = options(args)?;
+  let workers = workers(args.threads);
+  let mut writers = ResultWriters::new(args.stats)?;
 
-  Ok(())
+  pipeline::ordered(
+    CaseReader::new(io::stdin().lines(), args.validation),
+    (workers, 4 * workers),
+    |case| solve(case, args.top, &options),
+    |solved| writers.write_case(solved),
+  )?;
+
+  Ok(writers.finish()?)
 }
 
 fn main() {
-  let (s, r) = crossbeam::channel::unbounded();
+  let args = match Args::parse(std::env::args().skip(1)) {
+    Ok(args) => args,
+    Err(e) => {
+      eprintln!("{e}\n{}", cli::USAGE);
+      std::process::exit(2);
+    }
+  };
 
-  crossbeam::scope(|scope| {
-    scope.spawn(|_| main_thread(s).unwrap());
-    scope.spawn(|_| write_results_thread(&r));
-  })
-  .expect("both threads should exit without errors");
+  if let Err(e) = run(&args) {
+    eprintln!("{e}");
+    std::process::exit(1);
+  }
 }
@@ -0,0 +1,224 @@
+use std::{
+  collections::BTreeMap,
+  panic::{self, AssertUnwindSafe},
+  thread,
+};
+
+use crossbeam::{
+  channel::{bounded, Receiver, Sender},
+  thread::Scope,
+};
+
+/// Sends the items to the workers in input order, each one after taking a slot, and
+/// stops at the first error.
+fn feed<T, E>(
+  items: impl Iterator<Item = Result<T, E>>,
+  tasks: &Sender<(usize, T)>,
+  slots: &Receiver<()>,
+) -> Result<(), E> {
+  for (i, item) in items.enumerate() {
+    let item = item?;
+
+    if slots.recv().is_err() || tasks.send((i, item)).is_err() {
+      break;
+    }
+  }
+
+  Ok(())
+}
+
+/// Hands the results to `write` in input order, keeping the ones that arrive before an
+/// earlier one until it does, and stops at the first error. Every result written frees
+/// a slot. A worker that panicked is resumed here, in its turn.
+fn reorder<R, E>(
+  done: &Receiver<(usize, thread::Result<R>)>,
+  slots: &Sender<()>,
+  mut write: impl FnMut(R) -> Result<(), E>,
+) -> Result<(), E> {
+  let mut pending = BTreeMap::new();
+  let mut next = 0;
+
+  for (i, result) in done {
+    pending.insert(i, result);
+
+    while let Some(result) = pending.remove(&next) {
+      write(result.unwrap_or_else(|e| panic::resume_unwind(e)))?;
+      next += 1;
+      slots.send(()).ok();
+    }
+  }
+
+  Ok(())
+}
+
+/// Solves the items on `workers` threads and hands the results to `write` in input
+/// order, on a thread of its own. At most `capacity` items are read ahead of the last
+/// result written, which bounds the channels and the results waiting for an earlier
+/// one. With a single worker everything runs on the calling thread.
+///
+/// # Errors
+/// The first error of `write`, or else the first error of `items`. The items before
+/// it are still solved and written.
+///
+/// # Panics
+/// When `solve` panics, once the results before it are written.
+pub fn ordered<T: Send, R: Send, E: Send>(
+  items: impl Iterator<Item = Result<T, E>>,
+  (workers, capacity): (usize, usize),
+  solve: impl Fn(T) -> R + Sync,
+  mut write: impl FnMut(R) -> Result<(), E> + Send,
+) -> Result<(), E> {
+  if workers <= 1 {
+    return items
+      .map(|item| item.map(&solve))
+      .try_for_each(|r| r.and_then(&mut write));
+  }
+
+  threaded(items, (workers, capacity), &solve, write)
+}
+
+/// Channel holding `capacity` free slots.
+fn slots(capacity: usize) -> (Sender<()>, Receiver<()>) {
+  let (slots, free_slots) = bounded(capacity);
+  (0..capacity).for_each(|_| slots.send(()).expect("slots should have room"));
+  (slots, free_slots)
+}
+
+/// Starts `workers` threads solving the tasks they receive, each one sending its
+/// result, or its panic, to `done`.
+fn spawn_workers<'env, T: Send + 'env, R: Send + 'env>(
+  scope: &Scope<'env>,
+  workers: usize,
+  tasks: &Receiver<(usize, T)>,
+  done: &Sender<(usize, thread::Result<R>)>,
+  solve: &'env (impl Fn(T) -> R + Sync),
+) {
+  for _ in 0..workers {
+    let (tasks, done) = (tasks.clone(), done.clone());
+    scope.spawn(move |_| {
+      tasks.iter().try_for_each(|(i, t)| {
+        let solved = panic::catch_unwind(AssertUnwindSafe(|| solve(t)));
+        done.send((i, solved))
+      })
+    });
+  }
+}
+
+fn threaded<T: Send, R: Send, E: Send>(
+  items: impl Iterator<Item = Result<T, E>>,
+  (workers, capacity): (usize, usize),
+  solve: &(impl Fn(T) -> R + Sync),
+  write: impl FnMut(R) -> Result<(), E> + Send,
+) -> Result<(), E> {
+  let (tasks, tasks_received) = bounded(capacity);
+  let (done, done_received) = bounded(capacity);
+  let (slots, free_slots) = slots(capacity);
+
+  crossbeam::scope(|scope| {
+    spawn_workers(scope, workers, &tasks_received, &done, solve);
+    std::mem::drop(done);
+    // A writer that stops drops the slots, which stops reading too.
+    let writer = scope.spawn(move |_| reorder(&done_received, &slots, write));
+    // Closing the tasks lets the workers finish, which the scope waits for.
+    let fed = feed(items, &tasks, &free_slots);
+    std::mem::drop(tasks);
+    writer
+      .join()
+      .unwrap_or_else(|e| panic::resume_unwind(e))
+      .and(fed)
+  })
+  .expect("every pipeline thread should exit without errors")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::Duration,
+  };
+  use test_case::test_case;
+
+  #[test_case(1 ; "sequential")]
+  #[test_case(4 ; "parallel")]
+  fn test_ordered(workers: usize) {
+    let mut written = vec![];
+    let solve = |i| {
+      thread::sleep(Duration::from_millis((i * 7) % 5));
+      i * i
+    };
+
+    ordered((0..50_u64).map(Ok::<u64, ()>), (workers, 8), solve, |r| {
+      written.push(r);
+      Ok(())
+    })
+    .unwrap();
+
+    assert_eq!(written, (0..50_u64).map(|i| i * i).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn test_read_ahead_is_bounded() {
+    let (read, written) = (AtomicUsize::new(0), AtomicUsize::new(0));
+    let items = (0..40).map(|i| {
+      assert!(read.fetch_add(1, Ordering::SeqCst) <= written.load(Ordering::SeqCst) + 4);
+      Ok::<usize, ()>(i)
+    });
+
+    let write = |_| {
+      thread::sleep(Duration::from_millis(1));
+      written.fetch_add(1, Ordering::SeqCst);
+      Ok(())
+    };
+
+    ordered(items, (3, 4), |i| i, write).unwrap();
+    assert_eq!(written.load(Ordering::SeqCst), 40);
+  }
+
+  #[test]
+  fn test_error_stops_reading() {
+    let mut written = vec![];
+    let items = (0..10).map(|i| if i == 6 { Err(i) } else { Ok(i) });
+    let write = |r| {
+      written.push(r);
+      Ok(())
+    };
+
+    assert_eq!(ordered(items, (2, 3), |i| i, write), Err(6));
+    assert_eq!(written, [0, 1, 2, 3, 4, 5]);
+  }
+
+  #[test_case(1 ; "sequential")]
+  #[test_case(3 ; "parallel")]
+  fn test_panic_stops(workers: usize) {
+    let solve = |i| {
+      assert_ne!(i, 3, "solving should fail");
+      i
+    };
+    let mut written = vec![];
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+      ordered((0..100).map(Ok::<_, ()>), (workers, 4), solve, |r| {
+        written.push(r);
+        Ok(())
+      })
+    }));
+
+    assert!(outcome.is_err());
+    assert_eq!(written, [0, 1, 2]);
+  }
+
+  #[test_case(1 ; "sequential")]
+  #[test_case(3 ; "parallel")]
+  fn test_write_error_stops(workers: usize) {
+    let read = AtomicUsize::new(0);
+    let items = (0..100).map(|i| {
+      read.fetch_add(1, Ordering::SeqCst);
+      Ok(i)
+    });
+    let write = |r| if r == 5 { Err(r) } else { Ok(()) };
+
+    assert_eq!(ordered(items, (workers, 4), |i| i, write), Err(5));
+    assert!(read.load(Ordering::SeqCst) <= 10);
+  }
+}
@@ -1,144 +1,612 @@
+use std::{
+  cmp::Ordering,
+  sync::atomic::{self, AtomicU64},
+};
+
 use rayon::prelude::*;
 
 use crate::{
-  constants::EPS,
-  shapes::{point::Point, polygon::Polygon},
-  traits::{common_boundary::CommonBoundary, intersection::IntersectsHeuristic},
-  util::{ccw, cmp},
+  error::PuzzleError,
+  shapes::{overlap_sweep::OverlapSweep, point::Point, polygon::Polygon, transform::Transform},
+  tolerance::Tolerance,
+  util::cmp,
+};
+
+use self::{
+  budget::{Budget, Spending},
+  contacts::{contacts_within, Contact},
+  monitor::{CancelToken, Progress},
+  options::{MatchOptions, RotationSet},
+  prepared::PreparedPolygon,
+  search_space::{Rotation, SearchSpace},
+  stats::SearchStats,
 };
 
+mod bound;
+pub mod budget;
+mod congruence;
+pub mod contacts;
+pub mod exact;
+pub mod monitor;
+pub mod options;
+pub mod prepared;
+mod search_space;
+pub mod stats;
+pub mod symmetry;
+
+/// Optimal placement of two polygons. `transform1` and `transform2` map the vertices
+/// of the input polygons onto `polygon1` and `polygon2` respectively (in reverse
+/// order when the transform is reflected).
+pub struct MatchResult {
+  pub polygon1: Polygon,
+  pub polygon2: Polygon,
+  pub transform1: Transform,
+  pub transform2: Transform,
+  pub boundary: f64,
+  /// Tolerances the placement was found with, to compare its lengths and points.
+  pub tolerance: Tolerance,
+  /// Work done by the search that found the placement.
+  pub stats: SearchStats,
+}
+
+impl MatchResult {
+  /// `polygon1` moved by `transform1`, next to `polygon2` left where it is, with no
+  /// work recorded.
+  pub(crate) fn relative(
+    polygon1: &Polygon,
+    polygon2: &Polygon,
+    transform1: Transform,
+    boundary: f64,
+    tol: &Tolerance,
+  ) -> Self {
+    Self {
+      polygon1: transform1.apply_polygon(polygon1),
+      polygon2: polygon2.clone(),
+      transform1,
+      transform2: Transform::default(),
+      boundary,
+      tolerance: *tol,
+      stats: SearchStats::default(),
+    }
+  }
+}
+
+/// Placement found by a search that may have stopped before solving every pair.
+pub struct BudgetedMatch {
+  pub result: MatchResult,
+  /// Whether every pair that could improve on the placement was solved, in which case
+  /// it is the one `best_match_with` reports.
+  pub exhaustive: bool,
+}
+
 #[inline]
-fn range_contains(a: f64, b: f64, x: f64) -> bool {
+fn range_contains(a: f64, b: f64, x: f64, tol: &Tolerance) -> bool {
   if a > b {
-    b <= x + EPS && x - EPS <= a
+    b <= x + tol.linear && x - tol.linear <= a
   } else {
-    a <= x + EPS && x - EPS <= b
+    a <= x + tol.linear && x - tol.linear <= b
   }
 }
 
-fn face_left((a, b, c): (Point, Point, Point)) -> bool {
-  a.seg(b).face_left() || b.seg(c).face_left()
+fn face_left((a, b, c): (Point, Point, Point), tol: &Tolerance) -> bool {
+  a.seg(b).face_left(tol) || b.seg(c).face_left(tol)
 }
 
-fn face_right((a, b, c): (Point, Point, Point)) -> bool {
-  a.seg(b).face_right() || b.seg(c).face_right()
+fn face_right((a, b, c): (Point, Point, Point), tol: &Tolerance) -> bool {
+  a.seg(b).face_right(tol) || b.seg(c).face_right(tol)
 }
 
-fn collect_shifts(edges: &Polygon, vertices: &Polygon, right: bool, max_shift: f64) -> Vec<f64> {
+fn collect_shifts<'a>(
+  edges: &'a Polygon,
+  vertices: &'a Polygon,
+  right: bool,
+  max_shift: f64,
+  tol: &'a Tolerance,
+) -> impl Iterator<Item = f64> + 'a {
   edges
     .edges()
-    .filter(|w| !w.is_horizontal())
-    .flat_map(|wall| {
+    .filter(|w| !w.is_horizontal(tol))
+    .flat_map(move |wall| {
       vertices
         .vertices()
-        .filter(|(a, b, c)| ccw(*a, *b, *c))
-        .filter(move |(_, b, _)| range_contains(wall.p.y, wall.q.y, b.y))
-        .filter(move |v| !wall.face_right() || face_left(*v))
-        .filter(move |v| !wall.face_left() || face_right(*v))
-        .map(move |(_, b, _)| wall.horizontal_distance(b))
-        .map(|x| if right { x } else { -x })
-        .filter(|x| range_contains(EPS, max_shift - EPS, *x))
+        .filter(|(a, b, c)| tol.ccw(*a, *b, *c))
+        .filter(move |(_, b, _)| range_contains(wall.p.y, wall.q.y, b.y, tol))
+        .filter(move |v| !wall.face_right(tol) || face_left(*v, tol))
+        .filter(move |v| !wall.face_left(tol) || face_right(*v, tol))
+        .map(move |(_, b, _)| wall.horizontal_distance(b, tol))
+        .map(move |x| if right { x } else { -x })
+        .filter(move |x| range_contains(tol.linear, max_shift - tol.linear, *x, tol))
     })
-    .collect()
 }
 
-fn collect_all_shifts(polygon1: &Polygon, polygon2: &Polygon, base1: f64, base2: f64) -> Vec<f64> {
+/// Fills `shifts` with the candidate shifts in increasing order, and returns how many
+/// were collected. Shifts closer than the tolerance give the same placement, so only
+/// the first of them is kept.
+fn collect_all_shifts(
+  polygon1: &Polygon,
+  polygon2: &Polygon,
+  (base1, base2): (f64, f64),
+  tol: &Tolerance,
+  shifts: &mut Vec<f64>,
+) -> usize {
   let max_shift = base1 + base2;
-  let mut shifts = [
-    vec![base1, base2],
-    collect_shifts(polygon1, polygon2, true, max_shift),
-    collect_shifts(polygon2, polygon1, false, max_shift),
-  ]
-  .concat();
+  shifts.clear();
+  shifts.extend([base1, base2]);
+  shifts.extend(collect_shifts(polygon1, polygon2, true, max_shift, tol));
+  shifts.extend(collect_shifts(polygon2, polygon1, false, max_shift, tol));
   shifts.sort_unstable_by(cmp);
-  shifts
+
+  let collected = shifts.len();
+  shifts.dedup_by(|a, b| tol.equal(*a, *b));
+  collected
+}
+
+/// A pair, with a common boundary found for it and the shift giving it.
+type Solution = ((usize, usize), (f64, f64));
+
+/// Buffers reused by every pair a rayon worker solves, and the work done on the last
+/// one.
+#[derive(Default)]
+struct Buffers {
+  shifts: Vec<f64>,
+  edges: Vec<usize>,
+  /// Longest boundaries of the last pair and the shifts giving them.
+  best: Vec<(f64, f64)>,
+  stats: SearchStats,
+}
+
+impl Buffers {
+  /// Longest boundaries found for the last pair, or no boundary at shift 0 when the
+  /// polygons share none at any shift.
+  fn found(&self) -> &[(f64, f64)] {
+    if self.best.is_empty() {
+      &[(0_f64, 0_f64)]
+    } else {
+      &self.best
+    }
+  }
+}
+
+/// Fills `buffers` with the `k` longest common boundaries of the pair and the shifts
+/// giving them. The sliding polygon is never moved: every check reads its vertices with
+/// the shift added.
+fn optimal_shifts(
+  sliding: &Rotation,
+  fixed: &Rotation,
+  tol: &Tolerance,
+  (k, buffers): (usize, &mut Buffers),
+) {
+  let Buffers {
+    shifts,
+    edges,
+    best,
+    stats,
+  } = buffers;
+  let bases = (sliding.base, fixed.base);
+  let collected = collect_all_shifts(&sliding.polygon, &fixed.polygon, bases, tol, shifts);
+  let measured = best_shifts(sliding, fixed, tol, (shifts, edges), (k, best));
+  *stats = SearchStats::pair(collected, shifts.len(), measured);
 }
 
-#[allow(clippy::too_many_lines)]
-fn optimal_shift(mut polygon1: Polygon, polygon2: &Polygon, base1: f64, base2: f64) -> (f64, f64) {
-  let mut prev_shift_x = 0_f64;
-  let mut solution = (0_f64, 0_f64);
-  let mut prev = (0, 0);
+/// Fills `best` with the `k` longest common boundaries among the `shifts` where the
+/// polygons do not overlap and the shifts giving them, from the longest, and returns
+/// the number of boundaries measured. Of equal boundaries the smallest shift comes
+/// first.
+fn best_shifts(
+  sliding: &Rotation,
+  fixed: &Rotation,
+  tol: &Tolerance,
+  (shifts, edges): (&[f64], &mut Vec<usize>),
+  (k, best): (usize, &mut Vec<(f64, f64)>),
+) -> usize {
+  let mut sweep = OverlapSweep::new(&sliding.boxes, (&fixed.polygon, &fixed.boxes), tol);
+  let (polygon1, lines) = (&sliding.polygon, (&fixed.polygon, &fixed.lines));
+  let mut measured = 0;
+  best.clear();
 
-  for x in collect_all_shifts(&polygon1, polygon2, base1, base2) {
-    if x - prev_shift_x < 0.1 {
-      continue;
+  for &x in shifts.iter().filter(|&&x| !sweep.overlaps(polygon1, x)) {
+    let boundary = polygon1.common_boundary_at(x, lines, tol, edges);
+    let shortest = if best.len() < k { 0_f64 } else { best[k - 1].0 };
+    if k > 0 && boundary > shortest {
+      best.insert(best.partition_point(|(b, _)| *b >= boundary), (boundary, x));
+      best.truncate(k);
     }
+    measured += 1;
+  }
+
+  measured
+}
+
+/// Fills `buffers` with the `k` best shifts of the pair, or returns `false` when its
+/// edges cannot be made to touch.
+fn solve_pair(
+  space: &SearchSpace,
+  (i, j): (usize, usize),
+  (k, buffers): (usize, &mut Buffers),
+) -> bool {
+  let Some((sliding, fixed)) = space.alignment(i, j) else {
+    return false;
+  };
+  optimal_shifts(&sliding, fixed, &space.tolerance, (k, buffers));
+  space.counters.add(&buffers.stats);
 
-    polygon1
-      .vertices
-      .iter_mut()
-      .for_each(|p| p.x += x - prev_shift_x);
+  true
+}
 
-    if !polygon1.intersects(polygon2, &mut prev) {
-      let boundary = polygon1.common_boundary(polygon2);
-      if boundary > solution.0 {
-        solution.0 = boundary;
-        solution.1 = x;
+fn solve(
+  space: &SearchSpace,
+  pair: (usize, usize),
+  buffers: &mut Buffers,
+) -> Option<((usize, usize), (f64, f64))> {
+  solve_pair(space, pair, (1, buffers)).then(|| (pair, buffers.found()[0]))
+}
+
+/// The `k` best solutions of every pair that can be aligned, each rayon worker reusing
+/// its own buffers.
+fn evaluate<'a>(
+  space: &'a SearchSpace<'a>,
+  k: usize,
+) -> impl ParallelIterator<Item = Solution> + 'a {
+  space
+    .pairs()
+    .map_init(Buffers::default, move |buffers, pair| {
+      if solve_pair(space, pair, (k, buffers)) {
+        buffers.found().iter().map(|found| (pair, *found)).collect()
+      } else {
+        vec![]
       }
+    })
+    .flatten()
+}
+
+/// Orders solutions by boundary. Equal boundaries are ordered by pair and then by shift,
+/// the lowest ranking higher, so the placement reported does not depend on the order in
+/// which rayon compares them.
+fn rank(
+  (pair1, (boundary1, offset1)): &((usize, usize), (f64, f64)),
+  (pair2, (boundary2, offset2)): &((usize, usize), (f64, f64)),
+) -> Ordering {
+  boundary1
+    .total_cmp(boundary2)
+    .then(pair2.cmp(pair1))
+    .then(offset2.total_cmp(offset1))
+}
+
+/// Upper bound of the boundary of every pair that can be aligned, from the largest
+/// to the smallest, computed while `spending` allows.
+fn bounds(space: &SearchSpace, spending: &Spending) -> Vec<((usize, usize), f64)> {
+  let mut bounds: Vec<((usize, usize), f64)> = space
+    .pairs()
+    .filter(|_| !spending.stopped())
+    .filter_map(|(i, j)| {
+      let (sliding, fixed) = space.alignment(i, j)?;
+      Some(((i, j), space.upper_bound(&sliding, fixed)))
+    })
+    .collect();
+  bounds.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+  bounds
+}
+
+/// Solutions of the pairs whose bound is not below `best`, raising `best` to the
+/// longest boundary found, for as long as `spending` allows. Boundaries are never
+/// negative, so their bits are ordered like the numbers themselves.
+fn solve_bounded<'a>(
+  space: &'a SearchSpace<'a>,
+  bounds: &'a [((usize, usize), f64)],
+  (best, spending): (&'a AtomicU64, &'a Spending),
+) -> impl ParallelIterator<Item = ((usize, usize), (f64, f64))> + 'a {
+  bounds
+    .par_iter()
+    .filter(|(_, bound)| *bound >= f64::from_bits(best.load(atomic::Ordering::Relaxed)))
+    .filter(|_| spending.take())
+    .map_init(Buffers::default, |buffers, (pair, _)| {
+      let solution = solve(space, *pair, buffers)?;
+      best.fetch_max(solution.1 .0.to_bits(), atomic::Ordering::Relaxed);
+      Some(solution)
+    })
+    .flatten()
+}
+
+/// Same solutions as `evaluate` for every pair that may have the longest boundary, in
+/// the same order, where `seed` is the boundary of a placement known to be valid. Pairs
+/// are solved in batches from the highest upper bound down, so most of them can be
+/// skipped once a long boundary is found. Once `spending` runs out, the solutions found
+/// so far are returned. `progress` is called after each batch.
+fn evaluate_bounded(
+  space: &SearchSpace,
+  seed: f64,
+  watch: (&Spending, &mut dyn FnMut(Progress)),
+) -> Vec<((usize, usize), (f64, f64))> {
+  let best = AtomicU64::new(seed.to_bits());
+  let mut solutions = solve_batches(space, &bounds(space, watch.0), &best, watch);
+
+  solutions.sort_unstable_by_key(|(pair, _)| *pair);
+  solutions
+}
+
+/// Solutions of the pairs in `bounds`, one batch at a time for as long as `spending`
+/// allows, calling `progress` after each batch.
+fn solve_batches(
+  space: &SearchSpace,
+  bounds: &[((usize, usize), f64)],
+  best: &AtomicU64,
+  (spending, progress): (&Spending, &mut dyn FnMut(Progress)),
+) -> Vec<((usize, usize), (f64, f64))> {
+  let mut solutions = vec![];
+  let mut evaluated = 0;
+
+  for batch in bounds.chunks(4 * rayon::current_num_threads()) {
+    if spending.stopped() {
+      break;
     }
 
-    prev_shift_x = x;
+    solutions.par_extend(solve_bounded(space, batch, (best, spending)));
+    evaluated += batch.len();
+    progress(Progress {
+      evaluated,
+      total: bounds.len(),
+      best: f64::from_bits(best.load(atomic::Ordering::Relaxed)),
+    });
+  }
+
+  solutions
+}
+
+/// Contacts along runs of equal edges, whose boundaries are reached by some placement,
+/// measured while `spending` allows. With restricted rotations their placements may not
+/// be allowed, so none are used.
+fn seed_contacts(
+  prepared1: &PreparedPolygon,
+  prepared2: &PreparedPolygon,
+  tol: &Tolerance,
+  (options, spending): (&MatchOptions, &Spending),
+) -> Vec<Contact> {
+  match options.rotations {
+    RotationSet::Any => contacts_within(prepared1, prepared2, tol, spending),
+    _ => vec![],
+  }
+}
+
+/// Both polygons prepared for a single search.
+fn prepare(
+  polygon1: &Polygon,
+  polygon2: &Polygon,
+) -> Result<(PreparedPolygon, PreparedPolygon), PuzzleError> {
+  Ok((
+    PreparedPolygon::new(polygon1)?,
+    PreparedPolygon::new(polygon2)?,
+  ))
+}
+
+/// # Errors
+/// `DegeneratePolygon` when one of the polygons cannot be matched.
+pub fn best_match(polygon1: &Polygon, polygon2: &Polygon) -> Result<MatchResult, PuzzleError> {
+  best_match_with(polygon1, polygon2, &MatchOptions::default())
+}
+
+/// # Errors
+/// `DegeneratePolygon` when one of the polygons cannot be matched, and
+/// `InvalidOptions` when no rotation is allowed.
+pub fn best_match_with(
+  polygon1: &Polygon,
+  polygon2: &Polygon,
+  options: &MatchOptions,
+) -> Result<MatchResult, PuzzleError> {
+  let (prepared1, prepared2) = prepare(polygon1, polygon2)?;
+  best_match_prepared(&prepared1, &prepared2, options)
+}
+
+/// Same as `best_match_with`, reusing the rotations cached in both prepared polygons.
+///
+/// # Errors
+/// `InvalidOptions` when no rotation is allowed.
+pub fn best_match_prepared(
+  prepared1: &PreparedPolygon,
+  prepared2: &PreparedPolygon,
+  options: &MatchOptions,
+) -> Result<MatchResult, PuzzleError> {
+  let budget = Budget::default();
+  let spending = Spending::new(&budget, None);
+
+  options
+    .parallelism
+    .install(|| search(prepared1, prepared2, options, (&spending, &mut |_| {})))
+    .map(|found| found.result)
+}
+
+/// Same as `best_match_with`, but stops once `budget` is spent.
+///
+/// The best placement found so far is reported. Pairs are solved from the highest
+/// upper bound down, so long boundaries tend to be found early.
+///
+/// # Errors
+/// Same as `best_match_with`.
+pub fn best_match_with_deadline(
+  polygon1: &Polygon,
+  polygon2: &Polygon,
+  options: &MatchOptions,
+  budget: &Budget,
+) -> Result<BudgetedMatch, PuzzleError> {
+  let (prepared1, prepared2) = prepare(polygon1, polygon2)?;
+  let spending = Spending::new(budget, None);
+
+  options
+    .parallelism
+    .install(|| search(&prepared1, &prepared2, options, (&spending, &mut |_| {})))
+}
+
+/// Same as `best_match_with`, but reports its progress and stops once `cancel` is
+/// cancelled.
+///
+/// `progress` is called after each batch of pairs, on the thread running the search.
+///
+/// # Errors
+/// `Cancelled` when the search was cancelled before every pair was solved, and the
+/// errors of `best_match_with`.
+pub fn best_match_cancellable(
+  polygon1: &Polygon,
+  polygon2: &Polygon,
+  options: &MatchOptions,
+  cancel: &CancelToken,
+  mut progress: impl FnMut(Progress) + Send,
+) -> Result<MatchResult, PuzzleError> {
+  let (prepared1, prepared2) = prepare(polygon1, polygon2)?;
+  let budget = Budget::default();
+  let spending = Spending::new(&budget, Some(cancel));
+  let found = options
+    .parallelism
+    .install(|| search(&prepared1, &prepared2, options, (&spending, &mut progress)))?;
+
+  if spending.exhausted() {
+    return Err(PuzzleError::Cancelled);
   }
 
-  solution
+  Ok(found.result)
 }
 
-fn pairs(a: usize, b: usize) -> Vec<(usize, usize)> {
-  (0..a).flat_map(|i| (0..b).map(move |j| (i, j))).collect()
+fn search(
+  prepared1: &PreparedPolygon,
+  prepared2: &PreparedPolygon,
+  options: &MatchOptions,
+  watch: (&Spending, &mut dyn FnMut(Progress)),
+) -> Result<BudgetedMatch, PuzzleError> {
+  let tol = options.tolerance_for(prepared1.polygon(), prepared2.polygon());
+  let contacts = seed_contacts(prepared1, prepared2, &tol, (options, watch.0));
+  let contact = contacts.first();
+
+  if let Some(contact) = contact.filter(|_| options.approximate) {
+    return Ok(BudgetedMatch {
+      result: contact.placement(prepared1, prepared2, &tol),
+      exhaustive: false,
+    });
+  }
+
+  // Built after the contacts, as it prepares every rotation of both polygons.
+  let space = SearchSpace::new(prepared1, prepared2, options);
+  search_pairs(&space, contact, (prepared1, prepared2), watch)
 }
 
-fn bases(polygon: &Polygon, rotations: &[Polygon]) -> Vec<f64> {
-  (0..polygon.len())
+/// Solves the pairs of `space` while `spending` allows, where `contact` is the best
+/// one found by matching edge strings.
+fn search_pairs(
+  space: &SearchSpace,
+  contact: Option<&Contact>,
+  prepared: (&PreparedPolygon, &PreparedPolygon),
+  (spending, progress): (&Spending, &mut dyn FnMut(Progress)),
+) -> Result<BudgetedMatch, PuzzleError> {
+  let seed = contact.map_or(0_f64, |c| c.length);
+  let best = evaluate_bounded(space, seed, (spending, progress))
     .into_par_iter()
-    .map(|i| {
-      let polygon = &rotations[i];
-      polygon.vertices[i].dist(polygon.vertex_at((i + 1) as i32))
-    })
-    .collect()
+    .max_by(rank);
+  let exhaustive = !spending.exhausted();
+
+  Ok(BudgetedMatch {
+    result: pick(space, best, contact.filter(|_| !exhaustive), prepared)?,
+    exhaustive,
+  })
 }
 
-fn both_rotations(polygon1: &Polygon, polygon2: &Polygon) -> (Vec<Polygon>, Vec<Polygon>) {
-  let rotations1 = polygon1
-    .rotations()
-    .iter()
-    .map(Polygon::negate)
-    .collect::<Vec<Polygon>>();
-  let rotations2 = polygon2.rotations();
+/// Placement of the best solution, or of the contact when it is longer, or both
+/// polygons apart when there is neither. The contact is only given when the search
+/// stopped early, as an exhaustive search always finds a boundary as long.
+fn pick(
+  space: &SearchSpace,
+  best: Option<((usize, usize), (f64, f64))>,
+  contact: Option<&Contact>,
+  (prepared1, prepared2): (&PreparedPolygon, &PreparedPolygon),
+) -> Result<MatchResult, PuzzleError> {
+  let polygons = (prepared1.polygon(), prepared2.polygon());
 
-  (rotations1, rotations2)
+  let solved = best
+    .filter(|(_, (boundary, _))| contact.is_none_or(|c| *boundary >= c.length))
+    .and_then(|(pair, (boundary, offset))| space.placement(pair, offset, boundary, polygons));
+
+  let placement = match (solved, contact) {
+    (Some(placement), _) => placement,
+    (None, Some(contact)) => contact.placement(prepared1, prepared2, &space.tolerance),
+    (None, None) => space
+      .detached(polygons)
+      .ok_or_else(|| PuzzleError::InvalidOptions("no rotation is allowed".to_owned()))?,
+  };
+
+  Ok(MatchResult {
+    stats: space.counters.total(),
+    ..placement
+  })
 }
 
-#[must_use]
-#[allow(clippy::too_many_lines)]
-pub fn best_match(polygon1: &Polygon, polygon2: &Polygon) -> (Polygon, Polygon, f64) {
-  let (rotations1, rotations2) = both_rotations(polygon1, polygon2);
+/// Up to `k` placements with a common boundary, from the longest to the shortest one.
+///
+/// The `k` best shifts of every pair of edges are kept, so several placements may lay
+/// the same edges together. Placements congruent to a better one are skipped. When the
+/// polygons cannot touch, only the placement `best_match_with` would report is returned.
+///
+/// # Errors
+/// Same as `best_match_with`.
+pub fn best_matches_with(
+  polygon1: &Polygon,
+  polygon2: &Polygon,
+  k: usize,
+  options: &MatchOptions,
+) -> Result<Vec<MatchResult>, PuzzleError> {
+  let (prepared1, prepared2) = prepare(polygon1, polygon2)?;
+  let space = SearchSpace::new(&prepared1, &prepared2, options);
+  let solutions = ranked(&space, k, options);
+  let placements = distinct(&space, &solutions, k, (polygon1, polygon2));
 
-  let base1 = bases(polygon1, &rotations1);
-  let base2 = bases(polygon2, &rotations2);
+  if placements.is_empty() {
+    let best = solutions.first().copied();
+    return Ok(vec![pick(&space, best, None, (&prepared1, &prepared2))?]);
+  }
 
-  pairs(rotations1.len(), rotations2.len())
-    .par_iter()
-    .map(|(i, j)| {
-      (
-        *i,
-        *j,
-        optimal_shift(
-          rotations1[*i].clone(),
-          &rotations2[*j],
-          base1[*i],
-          base2[*j],
-        ),
-      )
-    })
-    .max_by(|(_, _, (a, _)), (_, _, (b, _))| cmp(a, b))
-    .map(|(i, j, (boundary, offset))| {
-      let mut p1 = rotations1[i].clone();
-      let p2 = rotations2[j].clone();
+  Ok(placements)
+}
 
-      p1.vertices.iter_mut().for_each(|p| p.x += offset);
+/// The `k` best solutions of every pair of `space`, from the longest boundary to the
+/// shortest one.
+fn ranked(space: &SearchSpace, k: usize, options: &MatchOptions) -> Vec<Solution> {
+  let mut solutions: Vec<Solution> = options.parallelism.install(|| evaluate(space, k).collect());
+  solutions.sort_by(|a, b| rank(b, a));
+  solutions
+}
 
-      (p1, p2, boundary)
+/// Placements of the ranked `solutions` with a common boundary, in order.
+fn placed<'a>(
+  space: &'a SearchSpace,
+  solutions: &'a [Solution],
+  polygons: (&'a Polygon, &'a Polygon),
+) -> impl Iterator<Item = MatchResult> + 'a {
+  solutions
+    .iter()
+    .filter(|(_, (b, _))| *b > space.tolerance.linear)
+    .filter_map(move |&(pair, (boundary, offset))| {
+      space.placement(pair, offset, boundary, polygons)
     })
-    .expect("there should be at least one solution")
+}
+
+/// Placements of the first `k` ranked `solutions` with a common boundary, skipping the
+/// ones congruent to an earlier one.
+fn distinct(
+  space: &SearchSpace,
+  solutions: &[Solution],
+  k: usize,
+  polygons: (&Polygon, &Polygon),
+) -> Vec<MatchResult> {
+  let tol = &space.tolerance;
+  let mut placements: Vec<MatchResult> = vec![];
+
+  for placement in placed(space, solutions, polygons) {
+    if placements.len() >= k {
+      break;
+    }
+
+    if placements
+      .iter()
+      .all(|p| !p.is_congruent_with(&placement, tol))
+    {
+      placements.push(placement);
+    }
+  }
+
+  placements
 }
@@ -0,0 +1,147 @@
+use std::{
+  f64::consts::{PI, TAU},
+  iter::once,
+  sync::Arc,
+};
+
+use crate::shapes::polygon::Polygon;
+
+struct Edge {
+  angle: f64,
+  length: f64,
+}
+
+/// Angle of the direction opposite to `angle`, both in `[-π, π]`.
+fn opposite(angle: f64) -> f64 {
+  if angle > 0_f64 {
+    angle - PI
+  } else {
+    angle + PI
+  }
+}
+
+/// Total length of the edges before each one, and of all of them last.
+fn running_totals(edges: &[Edge]) -> Arc<[f64]> {
+  once(0_f64)
+    .chain(edges.iter().scan(0_f64, |total, e| {
+      *total += e.length;
+      Some(*total)
+    }))
+    .collect()
+}
+
+/// Edges of an aligned polygon sorted by the angle of their direction, with the total
+/// length of the edges before each one.
+#[derive(Clone)]
+pub struct Directions {
+  edges: Arc<[Edge]>,
+  angles: Arc<[f64]>,
+  lengths: Arc<[f64]>,
+}
+
+impl Directions {
+  pub fn new(polygon: &Polygon) -> Self {
+    let mut edges: Vec<Edge> = polygon
+      .edges()
+      .map(|e| Edge {
+        angle: (e.q.y - e.p.y).atan2(e.q.x - e.p.x),
+        length: e.length(),
+      })
+      .collect();
+    edges.sort_unstable_by(|a, b| a.angle.total_cmp(&b.angle));
+
+    Self {
+      angles: edges.iter().map(|e| e.angle).collect(),
+      lengths: running_totals(&edges),
+      edges: edges.into(),
+    }
+  }
+
+  /// Total length of the edges with an angle in `[low, high]`.
+  fn between(&self, low: f64, high: f64) -> f64 {
+    let start = self.angles.partition_point(|a| *a < low);
+    let end = self.angles.partition_point(|a| *a <= high);
+    self.lengths[end] - self.lengths[start.min(end)]
+  }
+
+  /// Total length of the edges less than `window` radians away from `angle`.
+  fn around(&self, angle: f64, window: f64) -> f64 {
+    let (low, high) = (angle - window, angle + window);
+    let below = if low < -PI {
+      self.between(low + TAU, PI)
+    } else {
+      0_f64
+    };
+    let above = if high > PI {
+      self.between(-PI, high - TAU)
+    } else {
+      0_f64
+    };
+
+    self.between(low, high) + below + above
+  }
+
+  /// Longest common boundary two aligned polygons may have when the first one is
+  /// shifted along the X axis.
+  ///
+  /// Only antiparallel edges, up to `window` radians, can touch along a segment, so
+  /// each edge shares at most its own length and at most the total length of the
+  /// edges of the other polygon antiparallel to it. The bound is the smaller of both
+  /// sums, computed once from each side. It ignores where the edges are, so it costs a
+  /// binary search per edge instead of solving the pair.
+  pub fn upper_bound(&self, other: &Self, window: f64) -> f64 {
+    self.shared(other, window).min(other.shared(self, window))
+  }
+
+  /// Sum over the edges of `self` of the length each one may share with `other`.
+  fn shared(&self, other: &Self, window: f64) -> f64 {
+    self
+      .edges
+      .iter()
+      .map(|e| e.length.min(other.around(opposite(e.angle), window)))
+      .sum()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{shapes::point::Point, util::equal};
+  use test_case::test_case;
+
+  const SQUARE: [(f64, f64); 4] = [
+    (0_f64, 0_f64),
+    (2_f64, 0_f64),
+    (2_f64, 2_f64),
+    (0_f64, 2_f64),
+  ];
+  const BESIDE: [(f64, f64); 4] = [
+    (0_f64, 0_f64),
+    (5_f64, 0_f64),
+    (5_f64, 1_f64),
+    (0_f64, 1_f64),
+  ];
+  const DIAMOND: [(f64, f64); 4] = [
+    (1_f64, 0_f64),
+    (2_f64, 1_f64),
+    (1_f64, 2_f64),
+    (0_f64, 1_f64),
+  ];
+
+  fn directions(vertices: [(f64, f64); 4]) -> Directions {
+    Directions::new(&Polygon::new(
+      vertices
+        .iter()
+        .map(|(x, y)| Point { x: *x, y: *y })
+        .collect(),
+    ))
+  }
+
+  #[test_case(SQUARE, SQUARE, 8_f64 ; "every edge has an antiparallel one")]
+  #[test_case(SQUARE, BESIDE, 6_f64 ; "each edge counts at most the shorter length")]
+  #[test_case(SQUARE, DIAMOND, 0_f64 ; "no antiparallel edges")]
+  fn test_upper_bound(polygon1: [(f64, f64); 4], polygon2: [(f64, f64); 4], bound: f64) {
+    let upper_bound = directions(polygon1).upper_bound(&directions(polygon2), 1e-9);
+    assert!(equal(upper_bound, bound));
+  }
+}
@@ -0,0 +1,84 @@
+use std::{
+  sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+  time::{Duration, Instant},
+};
+
+use super::monitor::CancelToken;
+
+/// Limits on the work of a search. Unlimited by default.
+#[derive(Clone, Copy, Default)]
+pub struct Budget {
+  /// No pair is started after this instant.
+  pub deadline: Option<Instant>,
+  /// Largest number of pairs to solve.
+  pub pairs: Option<usize>,
+}
+
+impl Budget {
+  /// Budget of `timeout` from now.
+  #[must_use]
+  pub fn timeout(timeout: Duration) -> Self {
+    Self {
+      deadline: Instant::now().checked_add(timeout),
+      pairs: None,
+    }
+  }
+}
+
+/// Work a search has done against its budget, shared by the threads solving pairs.
+pub(super) struct Spending<'a> {
+  budget: &'a Budget,
+  cancel: Option<&'a CancelToken>,
+  pairs: AtomicUsize,
+  exhausted: AtomicBool,
+}
+
+impl<'a> Spending<'a> {
+  pub(super) const fn new(budget: &'a Budget, cancel: Option<&'a CancelToken>) -> Self {
+    Self {
+      budget,
+      cancel,
+      pairs: AtomicUsize::new(0),
+      exhausted: AtomicBool::new(false),
+    }
+  }
+
+  /// Takes a pair from the budget, or returns `false` once it is spent.
+  pub(super) fn take(&self) -> bool {
+    let over = self
+      .budget
+      .pairs
+      .is_some_and(|limit| self.pairs.fetch_add(1, Ordering::Relaxed) >= limit);
+
+    if over {
+      self.exhausted.store(true, Ordering::Relaxed);
+    }
+
+    !self.stopped()
+  }
+
+  /// Whether no pair should be started anymore, because the budget is spent or the
+  /// search is cancelled.
+  pub(super) fn stopped(&self) -> bool {
+    let late = self
+      .budget
+      .deadline
+      .is_some_and(|deadline| Instant::now() >= deadline);
+
+    if late || self.cancelled() {
+      self.exhausted.store(true, Ordering::Relaxed);
+    }
+
+    self.exhausted()
+  }
+
+  /// Whether the caller cancelled the search.
+  pub(super) fn cancelled(&self) -> bool {
+    self.cancel.is_some_and(CancelToken::is_cancelled)
+  }
+
+  /// Whether the search stopped before deciding every pair.
+  pub(super) fn exhausted(&self) -> bool {
+    self.exhausted.load(Ordering::Relaxed)
+  }
+}
@@ -0,0 +1,63 @@
+use crate::{
+  shapes::{point::Point, polygon::Polygon, transform::Transform},
+  tolerance::Tolerance,
+  util::angle,
+};
+
+use super::MatchResult;
+
+fn same_cycle(a: &[Point], b: &[Point], tol: &Tolerance) -> bool {
+  if a.len() != b.len() || a.is_empty() {
+    return false;
+  }
+
+  (0..b.len())
+    .filter(|s| tol.points_equal(a[0], b[*s]))
+    .any(|s| {
+      a.iter()
+        .enumerate()
+        .all(|(i, p)| tol.points_equal(*p, b[(i + s) % b.len()]))
+    })
+}
+
+/// Rotations and translations that lay the first edge of `from` onto each edge of `to`.
+fn motions<'a>(from: &'a Polygon, to: &'a Polygon) -> impl Iterator<Item = Transform> + 'a {
+  (0..to.len()).map(move |k| {
+    let (p, q) = (to.vertex_at(k as i32), to.vertex_at(k as i32 + 1));
+    let rotation = angle(from.vertex_at(1) - from.vertex_at(0), q - p);
+
+    Transform {
+      rotation,
+      reflected: false,
+      translation: p - from.vertex_at(0).rot_ccw(rotation),
+    }
+  })
+}
+
+impl MatchResult {
+  /// Whether both placements are the same up to a rotation and translation of the
+  /// whole arrangement, which happens when one of the polygons is symmetric.
+  #[must_use]
+  pub fn is_congruent(&self, other: &Self) -> bool {
+    self.is_congruent_with(other, &Tolerance::default())
+  }
+
+  #[must_use]
+  pub fn is_congruent_with(&self, other: &Self, tol: &Tolerance) -> bool {
+    if self.polygon2.is_empty() || self.polygon1.len() != other.polygon1.len() {
+      return false;
+    }
+
+    motions(&self.polygon2, &other.polygon2).any(|m| {
+      same_cycle(
+        &m.apply_polygon(&self.polygon2).vertices,
+        &other.polygon2.vertices,
+        tol,
+      ) && same_cycle(
+        &m.apply_polygon(&self.polygon1).vertices,
+        &other.polygon1.vertices,
+        tol,
+      )
+    })
+  }
+}
@@ -0,0 +1,439 @@
+use std::{
+  collections::HashMap,
+  f64::consts::{PI, TAU},
+};
+
+use crate::{
+  shapes::{overlap_sweep::OverlapSweep, point::Point, polygon::Polygon, transform::Transform},
+  tolerance::Tolerance,
+  util::{angle, cmp},
+};
+
+use super::{
+  budget::{Budget, Spending},
+  prepared::PreparedPolygon,
+  search_space::{negated, rotation, Rotation},
+  stats::SearchStats,
+  MatchResult,
+};
+
+/// Symbol of an edge string: the length of an edge or the turn at the vertex after it,
+/// both rounded to a step.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Token {
+  Length(i64),
+  Turn(i64),
+}
+
+/// Steps lengths and turns are rounded to. They are much coarser than the tolerance,
+/// so that equal values seldom fall on both sides of a step. Tokens that are equal by
+/// chance only propose a contact that is then measured.
+struct Steps {
+  length: f64,
+  turn: f64,
+}
+
+impl Steps {
+  fn new(shortest: f64, tol: &Tolerance) -> Self {
+    Self {
+      length: 64_f64 * tol.linear,
+      turn: 64_f64 * tol.linear / shortest,
+    }
+  }
+
+  fn length(&self, length: f64) -> Token {
+    Token::Length((length / self.length).round() as i64)
+  }
+
+  fn turn(&self, turn: f64) -> Token {
+    Token::Turn((turn / self.turn).round() as i64)
+  }
+}
+
+/// Turn at vertex `i`, between `-π` and `π`.
+fn turn(polygon: &Polygon, i: i32) -> f64 {
+  let (a, b, c) = polygon.vertices_at(i);
+  (angle(b - a, c - b) + PI).rem_euclid(TAU) - PI
+}
+
+/// Edge string of the polygon twice around, so that runs can wrap past its first edge.
+/// Edge `e` gives tokens `2e` and `2e + 1`.
+fn forward(polygon: &Polygon, steps: &Steps) -> Vec<Token> {
+  let n = polygon.len() as i32;
+
+  (0..2 * n)
+    .flat_map(|e| {
+      let edge = polygon.edge((e % n) as usize);
+      [
+        steps.length(edge.length()),
+        steps.turn(turn(polygon, e + 1)),
+      ]
+    })
+    .collect()
+}
+
+/// Edge string of the polygon walked backwards twice around, starting at edge `0`.
+/// Walking the other way round negates the turns, so a run of this string equal to a
+/// run of `forward` of another polygon is a chain of edges both polygons can share.
+fn backward(polygon: &Polygon, steps: &Steps) -> Vec<Token> {
+  let n = polygon.len();
+
+  (0..2 * n)
+    .flat_map(|m| {
+      let j = (n - m % n) % n;
+      [
+        steps.length(polygon.edge(j).length()),
+        steps.turn(-turn(polygon, j as i32)),
+      ]
+    })
+    .collect()
+}
+
+#[derive(Clone)]
+struct State {
+  len: usize,
+  link: Option<usize>,
+  next: HashMap<Token, usize>,
+  /// Position in the text where the first occurrence of the state ends.
+  end: usize,
+}
+
+impl State {
+  fn new(len: usize, end: usize) -> Self {
+    Self {
+      len,
+      link: Some(0),
+      next: HashMap::new(),
+      end,
+    }
+  }
+}
+
+/// Automaton recognizing every substring of a text, built in linear time.
+struct SuffixAutomaton {
+  states: Vec<State>,
+}
+
+impl SuffixAutomaton {
+  fn new(text: &[Token]) -> Self {
+    let mut automaton = Self {
+      states: vec![State {
+        link: None,
+        ..State::new(0, 0)
+      }],
+    };
+
+    text
+      .iter()
+      .enumerate()
+      .fold(0, |last, (end, token)| automaton.extend(last, *token, end));
+
+    automaton
+  }
+
+  fn push(&mut self, state: State) -> usize {
+    self.states.push(state);
+    self.states.len() - 1
+  }
+
+  /// Points the transitions on `token` to `to`, from `from` along the suffix links for
+  /// as long as `redirect` accepts the current transition. Returns the first state it
+  /// stops at.
+  fn redirect(
+    &mut self,
+    from: usize,
+    token: Token,
+    redirect: impl Fn(Option<&usize>) -> bool,
+    to: usize,
+  ) -> Option<usize> {
+    let mut p = Some(from);
+
+    while let Some(state) = p.filter(|s| redirect(self.states[*s].next.get(&token))) {
+      self.states[state].next.insert(token, to);
+      p = self.states[state].link;
+    }
+
+    p
+  }
+
+  /// Adds `token`, at position `end` of the text, after the state of the whole text
+  /// read so far, and returns the state of the new text.
+  fn extend(&mut self, last: usize, token: Token, end: usize) -> usize {
+    let current = self.push(State::new(self.states[last].len + 1, end));
+
+    if let Some(p) = self.redirect(last, token, |t| t.is_none(), current) {
+      self.states[current].link = Some(self.suffix_link(p, token));
+    }
+
+    current
+  }
+
+  /// State to link a new state to when `p` already has a transition on `token`,
+  /// splitting the state it leads to when that state also holds longer strings.
+  fn suffix_link(&mut self, p: usize, token: Token) -> usize {
+    let q = self.states[p].next[&token];
+
+    if self.states[p].len + 1 == self.states[q].len {
+      return q;
+    }
+
+    let clone = self.push(State {
+      len: self.states[p].len + 1,
+      ..self.states[q].clone()
+    });
+    self.redirect(p, token, |t| t == Some(&q), clone);
+    self.states[q].link = Some(clone);
+
+    clone
+  }
+
+  /// Reads `token` after a substring of `length` tokens ending at `state`, shortening
+  /// the substring until it can be extended.
+  fn step(&self, (mut state, mut length): (usize, usize), token: &Token) -> (usize, usize) {
+    while let Some(link) = self.states[state]
+      .link
+      .filter(|_| !self.states[state].next.contains_key(token))
+    {
+      state = link;
+      length = self.states[state].len;
+    }
+
+    self.states[state]
+      .next
+      .get(token)
+      .map_or((state, 0), |next| (*next, length + 1))
+  }
+
+  /// Length of the longest substring of the text ending at each position of `pattern`,
+  /// and the position where its first occurrence in the text ends.
+  fn matches(&self, pattern: &[Token]) -> Vec<(usize, usize)> {
+    pattern
+      .iter()
+      .scan((0, 0), |current, token| {
+        *current = self.step(*current, token);
+        Some((current.1, self.states[current.0].end))
+      })
+      .collect()
+  }
+}
+
+/// Placement laying an edge of the first polygon on an edge of the second one, found
+/// where a run of their edge strings match.
+pub struct Contact {
+  /// Edge of the first polygon and edge of the second one laid on it, both at the start
+  /// of the run.
+  pub edges: (usize, usize),
+  /// Shift of the sliding polygon in the pair aligned by both edges, which makes the
+  /// edges coincide.
+  pub shift: f64,
+  /// Common boundary of the placement, including the parts of the edges around the run.
+  pub length: f64,
+}
+
+impl Contact {
+  /// The placement itself, where `prepared1` and `prepared2` are the polygons the
+  /// contact was found for.
+  #[must_use]
+  pub fn placement(
+    &self,
+    prepared1: &PreparedPolygon,
+    prepared2: &PreparedPolygon,
+    tol: &Tolerance,
+  ) -> MatchResult {
+    let (sliding, fixed) = aligned(prepared1.polygon(), prepared2.polygon(), self.edges);
+    let shift = Point {
+      x: self.shift,
+      y: 0_f64,
+    };
+
+    MatchResult {
+      polygon1: Transform::translation(shift).apply_polygon(&sliding.polygon),
+      polygon2: fixed.polygon.clone(),
+      transform1: sliding.transform.then(&Transform::translation(shift)),
+      transform2: fixed.transform,
+      boundary: self.length,
+      tolerance: *tol,
+      stats: SearchStats::default(),
+    }
+  }
+}
+
+/// Positions in the text and in the pattern of the first edge of the run of `length`
+/// tokens ending at `end` and `p` respectively, or `None` when it has no whole edge. A
+/// run is trimmed to whole edges, as the turns before and after it may differ.
+fn run_start(p: usize, (length, end): (usize, usize)) -> Option<(usize, usize)> {
+  let start = p + 1 - length;
+  let trim = start % 2;
+
+  (length > trim + p % 2).then(|| {
+    (
+      (end + 1 - length + trim).div_euclid(2),
+      (start + trim).div_euclid(2),
+    )
+  })
+}
+
+/// Pairs of edges starting the longest runs that end at each position of `backward`,
+/// where `matches` comes from the automaton of `forward`.
+fn runs(matches: &[(usize, usize)], (n1, n2): (usize, usize)) -> Vec<(usize, usize)> {
+  let mut pairs: Vec<(usize, usize)> = matches
+    .iter()
+    .enumerate()
+    .filter(|(p, (length, _))| matches.get(p + 1).is_none_or(|(l, _)| *l != length + 1))
+    .filter_map(|(p, run)| run_start(p, *run))
+    .map(|(text, pattern)| (text % n1, (n2 - pattern % n2) % n2))
+    .collect();
+
+  pairs.sort_unstable();
+  pairs.dedup();
+  pairs
+}
+
+/// Pairs of edges starting a run both polygons may share.
+fn edge_runs(
+  prepared1: &PreparedPolygon,
+  prepared2: &PreparedPolygon,
+  tol: &Tolerance,
+) -> Vec<(usize, usize)> {
+  let (polygon1, polygon2) = (prepared1.polygon(), prepared2.polygon());
+  let steps = Steps::new(
+    prepared1.shortest_edge().min(prepared2.shortest_edge()),
+    tol,
+  );
+  let matches =
+    SuffixAutomaton::new(&forward(polygon1, &steps)).matches(&backward(polygon2, &steps));
+
+  runs(&matches, (polygon1.len(), polygon2.len()))
+}
+
+/// The sliding rotation of `polygon1` by its edge `i` and the fixed rotation of
+/// `polygon2` by its edge `j`, built for this pair alone.
+fn aligned(polygon1: &Polygon, polygon2: &Polygon, (i, j): (usize, usize)) -> (Rotation, Rotation) {
+  (negated(&rotation(polygon1, i)), rotation(polygon2, j))
+}
+
+/// Common boundary of the pair once the edges it is aligned by coincide, or `None`
+/// when the polygons overlap there.
+fn measure(sliding: &Rotation, fixed: &Rotation, tol: &Tolerance) -> Option<f64> {
+  let mut sweep = OverlapSweep::new(&sliding.boxes, (&fixed.polygon, &fixed.boxes), tol);
+
+  (!sweep.overlaps(&sliding.polygon, sliding.base)).then(|| {
+    sliding.polygon.common_boundary_at(
+      sliding.base,
+      (&fixed.polygon, &fixed.lines),
+      tol,
+      &mut vec![],
+    )
+  })
+}
+
+/// Contacts along chains of edges the polygons have in common, from the longest to the
+/// shortest, for any rotation of the first polygon.
+///
+/// A chain both polygons can share is a run of equal edge lengths and opposite turns,
+/// so the runs are found by matching the edge string of the first polygon against the
+/// reversed one of the second with a suffix automaton, in linear time. Only the first
+/// occurrence of each run in the first polygon is proposed. Each contact is then
+/// measured exactly on the two rotations it needs, built for it alone, discarding those
+/// where the polygons overlap, so the cost grows with the contacts proposed rather
+/// than with every rotation of both polygons.
+#[must_use]
+pub fn contacts(
+  prepared1: &PreparedPolygon,
+  prepared2: &PreparedPolygon,
+  tol: &Tolerance,
+) -> Vec<Contact> {
+  contacts_within(
+    prepared1,
+    prepared2,
+    tol,
+    &Spending::new(&Budget::default(), None),
+  )
+}
+
+/// Same as `contacts`, measuring contacts only until `spending` stops.
+pub(super) fn contacts_within(
+  prepared1: &PreparedPolygon,
+  prepared2: &PreparedPolygon,
+  tol: &Tolerance,
+  spending: &Spending,
+) -> Vec<Contact> {
+  let mut contacts: Vec<Contact> = edge_runs(prepared1, prepared2, tol)
+    .into_iter()
+    .take_while(|_| !spending.stopped())
+    .filter_map(|edges| {
+      let (sliding, fixed) = aligned(prepared1.polygon(), prepared2.polygon(), edges);
+      Some(Contact {
+        edges,
+        shift: sliding.base,
+        length: measure(&sliding, &fixed, tol)?,
+      })
+    })
+    .collect();
+
+  contacts.sort_by(|a, b| cmp(&b.length, &a.length));
+  contacts
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn polygon(vertices: &[(f64, f64)]) -> PreparedPolygon {
+    PreparedPolygon::try_from(Polygon::new(
+      vertices.iter().map(|&(x, y)| Point { x, y }).collect(),
+    ))
+    .unwrap()
+  }
+
+  fn tokens(values: &[i64]) -> Vec<Token> {
+    values.iter().map(|v| Token::Length(*v)).collect()
+  }
+
+  #[test]
+  fn test_matches() {
+    let automaton = SuffixAutomaton::new(&tokens(&[1, 2, 3, 1, 2, 4]));
+    let matches = automaton.matches(&tokens(&[2, 3, 1, 2, 3, 5, 1]));
+
+    assert_eq!(
+      matches.iter().map(|(l, _)| *l).collect::<Vec<_>>(),
+      [1, 2, 3, 4, 3, 0, 1]
+    );
+    assert_eq!(matches[3].1, 4);
+    assert_eq!(matches[4].1, 2);
+  }
+
+  /// Two steps cut out of the top of a rectangle.
+  const NOTCHED: [(f64, f64); 8] = [
+    (0_f64, 0_f64),
+    (6_f64, 0_f64),
+    (6_f64, 3_f64),
+    (4_f64, 3_f64),
+    (4_f64, 2_f64),
+    (2_f64, 2_f64),
+    (2_f64, 1_f64),
+    (0_f64, 1_f64),
+  ];
+
+  /// The same steps sticking out of the bottom of another rectangle.
+  const STEPPED: [(f64, f64); 7] = [
+    (0_f64, 1_f64),
+    (2_f64, 1_f64),
+    (2_f64, 2_f64),
+    (4_f64, 2_f64),
+    (4_f64, 3_f64),
+    (4_f64, 5_f64),
+    (0_f64, 5_f64),
+  ];
+
+  #[test]
+  fn test_staircases_fit() {
+    let (notched, stepped) = (polygon(&NOTCHED), polygon(&STEPPED));
+    let contacts = contacts(&notched, &stepped, &Tolerance::default());
+
+    assert!(!contacts.is_empty());
+    assert!((contacts[0].length - 6_f64).abs() < 1e-9);
+    assert!(contacts.windows(2).all(|w| w[0].length >= w[1].length));
+    assert!(!notched.is_rotated() && !stepped.is_rotated());
+  }
+}
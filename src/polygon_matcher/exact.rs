@@ -0,0 +1,764 @@
+use std::cmp::Ordering;
+
+use num_traits::ToPrimitive;
+
+use crate::{
+  constants::EPS,
+  error::PuzzleError,
+  shapes::{point::Point, polygon::Polygon, segment::Segment, transform::Transform},
+  tolerance::Tolerance,
+};
+
+use self::quadratic::{Field, Quadratic};
+use super::{
+  options::MatchOptions,
+  prepare, ranked,
+  search_space::{mirror, SearchSpace},
+  stats::SearchStats,
+  MatchResult,
+};
+
+mod quadratic;
+
+/// Largest coordinate accepted, so that products of squared lengths fit in an `i64`.
+const MAX_COORDINATE: f64 = 16_384_f64;
+
+#[derive(Clone)]
+struct ExactPoint {
+  x: Quadratic,
+  y: Quadratic,
+}
+
+impl ExactPoint {
+  fn sub(&self, other: &Self) -> Self {
+    Self {
+      x: &self.x - &other.x,
+      y: &self.y - &other.y,
+    }
+  }
+
+  fn scale(&self, k: &Quadratic) -> Self {
+    Self {
+      x: &self.x * k,
+      y: &self.y * k,
+    }
+  }
+
+  fn cross(&self, other: &Self) -> Quadratic {
+    &(&self.x * &other.y) - &(&self.y * &other.x)
+  }
+
+  fn dot(&self, other: &Self) -> Quadratic {
+    &(&self.x * &other.x) + &(&self.y * &other.y)
+  }
+
+  fn to_point(&self) -> Point {
+    Point {
+      x: self.x.to_f64(),
+      y: self.y.to_f64(),
+    }
+  }
+}
+
+impl PartialEq for ExactPoint {
+  fn eq(&self, other: &Self) -> bool {
+    self.x == other.x && self.y == other.y
+  }
+}
+
+fn orientation(o: &ExactPoint, a: &ExactPoint, b: &ExactPoint) -> Ordering {
+  a.sub(o).cross(&b.sub(o)).sign()
+}
+
+fn ccw(o: &ExactPoint, a: &ExactPoint, b: &ExactPoint) -> bool {
+  orientation(o, a, b) == Ordering::Greater
+}
+
+fn integer_vertices(polygon: &Polygon) -> Option<Vec<(i64, i64)>> {
+  let integer = |c: f64| (c.fract() == 0_f64 && c.abs() <= MAX_COORDINATE).then_some(c as i64);
+
+  polygon
+    .vertices
+    .iter()
+    .map(|p| Some((integer(p.x)?, integer(p.y)?)))
+    .collect()
+}
+
+fn triple<T>(items: &[T], i: usize) -> (&T, &T, &T) {
+  let n = items.len();
+  (&items[(i + n - 1) % n], &items[i % n], &items[(i + 1) % n])
+}
+
+/// Both points of the segment `p q` strictly contain `r` between them.
+fn strictly_inside(p: &ExactPoint, q: &ExactPoint, r: &ExactPoint) -> bool {
+  orientation(p, q, r) == Ordering::Equal
+    && q.sub(p).dot(&r.sub(p)).sign() == Ordering::Greater
+    && p.sub(q).dot(&r.sub(q)).sign() == Ordering::Greater
+}
+
+fn crosses(a: (&ExactPoint, &ExactPoint), b: (&ExactPoint, &ExactPoint)) -> bool {
+  let opposite = |x: Ordering, y: Ordering| x != Ordering::Equal && x == y.reverse();
+
+  opposite(orientation(a.0, a.1, b.0), orientation(a.0, a.1, b.1))
+    && opposite(orientation(b.0, b.1, a.0), orientation(b.0, b.1, a.1))
+}
+
+/// Vertex `a1` lies inside the edge `b1 b2` and one of its edges enters the polygon
+/// on the left of it.
+fn enters_edge(
+  (a0, a1, a2): (&ExactPoint, &ExactPoint, &ExactPoint),
+  b1: &ExactPoint,
+  b2: &ExactPoint,
+) -> bool {
+  strictly_inside(b1, b2, a1) && (ccw(b1, b2, a2) || ccw(b1, b2, a0))
+}
+
+/// Whether the direction `d` is at an angle in `[π, 2π)` counterclockwise from `start`.
+fn lower_half(start: &ExactPoint, d: &ExactPoint) -> bool {
+  match start.cross(d).sign() {
+    Ordering::Greater => false,
+    Ordering::Less => true,
+    Ordering::Equal => start.dot(d).sign() == Ordering::Less,
+  }
+}
+
+/// Counterclockwise angle from `start` to `x` is smaller than the one to `y`.
+fn angle_less(start: &ExactPoint, x: &ExactPoint, y: &ExactPoint) -> bool {
+  match (lower_half(start, x), lower_half(start, y)) {
+    (false, true) => true,
+    (true, false) => false,
+    _ => x.cross(y).sign() == Ordering::Greater,
+  }
+}
+
+fn same_direction(x: &ExactPoint, y: &ExactPoint) -> bool {
+  x.cross(y).sign() == Ordering::Equal && x.dot(y).sign() == Ordering::Greater
+}
+
+/// Both polygons share the vertex `a1 = b1` and an edge of the first one starts
+/// inside the angle of the second one.
+fn shares_vertex(
+  (a0, a1, a2): (&ExactPoint, &ExactPoint, &ExactPoint),
+  (b0, b1, b2): (&ExactPoint, &ExactPoint, &ExactPoint),
+) -> bool {
+  if a1 != b1 {
+    return false;
+  }
+
+  let (start, end) = (b2.sub(b1), b0.sub(b1));
+  let (d0, d2) = (a0.sub(a1), a2.sub(a1));
+
+  (!same_direction(&start, &d0) && angle_less(&start, &d0, &end)) || angle_less(&start, &d2, &end)
+}
+
+/// Exact counterpart of the overlap test of `Polygon::intersects_with`.
+fn overlaps(polygon1: &[ExactPoint], polygon2: &[ExactPoint]) -> bool {
+  (0..polygon1.len()).any(|i| {
+    (0..polygon2.len()).any(|j| {
+      let (a, b) = (triple(polygon1, i), triple(polygon2, j));
+
+      crosses((a.1, a.2), (b.1, b.2))
+        || enters_edge(a, b.1, b.2)
+        || enters_edge(b, a.1, a.2)
+        || shares_vertex(a, b)
+    })
+  })
+}
+
+fn max(a: &Quadratic, b: &Quadratic) -> Quadratic {
+  if a > b {
+    a.clone()
+  } else {
+    b.clone()
+  }
+}
+
+fn min(a: &Quadratic, b: &Quadratic) -> Quadratic {
+  if a < b {
+    a.clone()
+  } else {
+    b.clone()
+  }
+}
+
+/// Length of the part of `from to` lying on the segment `p q`.
+fn overlap_length(p: &ExactPoint, q: &ExactPoint, from: &ExactPoint, to: &ExactPoint) -> f64 {
+  if orientation(p, q, from) != Ordering::Equal || orientation(p, q, to) != Ordering::Equal {
+    return 0_f64;
+  }
+
+  let direction = q.sub(p);
+  let (length, t_from, t_to) = (
+    direction.dot(&direction),
+    direction.dot(&from.sub(p)),
+    direction.dot(&to.sub(p)),
+  );
+  let extent = &min(&length, &max(&t_from, &t_to)) - &min(&t_from, &t_to).positive_part();
+
+  if extent.sign() == Ordering::Greater {
+    (&(&extent * &extent) / &length).to_f64().sqrt()
+  } else {
+    0_f64
+  }
+}
+
+/// Vector along every edge of `polygon`, exactly and in floating point.
+fn edge_directions(polygon: &[ExactPoint]) -> Vec<(ExactPoint, Point)> {
+  (0..polygon.len())
+    .map(|i| {
+      let (_, p, q) = triple(polygon, i);
+      let direction = q.sub(p);
+      let rough = direction.to_point();
+      (direction, rough)
+    })
+    .collect()
+}
+
+/// Pairs of an edge of each polygon that are parallel, the only ones that can share a
+/// segment. Moving either polygon does not change them. Only the pairs close to
+/// parallel in floating point are checked exactly.
+fn parallel_edges(polygon1: &[ExactPoint], polygon2: &[ExactPoint]) -> Vec<(usize, usize)> {
+  let (directions1, directions2) = (edge_directions(polygon1), edge_directions(polygon2));
+  let close = |a: Point, b: Point| a.cross(b).abs() <= EPS * a.x.hypot(a.y) * b.x.hypot(b.y);
+
+  (0..polygon1.len())
+    .flat_map(|i| (0..polygon2.len()).map(move |j| (i, j)))
+    .filter(|&(i, j)| {
+      let ((a, rough_a), (b, rough_b)) = (&directions1[i], &directions2[j]);
+      close(*rough_a, *rough_b) && a.cross(b).is_zero()
+    })
+    .collect()
+}
+
+/// Common boundary of the fixed polygon and the sliding one of `contact` moved by
+/// `shift`, counting only the pairs of edges in `pairs`.
+fn common_boundary(contact: &Contact, shift: &Quadratic, pairs: &[(usize, usize)]) -> f64 {
+  let moved = contact.direction.scale(shift);
+
+  pairs
+    .iter()
+    .map(|&(i, j)| {
+      let (_, p, q) = triple(&contact.sliding, i);
+      let (_, r, s) = triple(&contact.fixed, j);
+      overlap_length(&p.sub(&moved), &q.sub(&moved), r, s)
+    })
+    .sum()
+}
+
+/// Where the sliding polygon of a contact may start or stop touching the fixed one.
+#[derive(Clone, Copy)]
+enum Event {
+  /// A vertex reaching the supporting line of a wall of the other polygon: whether the
+  /// wall is a fixed edge, its index and the index of the vertex.
+  Wall(bool, usize, usize),
+  /// The start of the sliding edge reaching the end of the fixed one.
+  Base,
+  /// The end of the sliding edge reaching the start of the fixed one.
+  Full,
+}
+
+/// Every event of a contact between polygons of `sliding` and `fixed` vertices.
+fn events(sliding: usize, fixed: usize) -> impl Iterator<Item = Event> {
+  let walls = |fixed_wall, walls, vertices| {
+    (0..walls)
+      .flat_map(move |wall| (0..vertices).map(move |vertex| Event::Wall(fixed_wall, wall, vertex)))
+  };
+
+  walls(true, fixed, sliding)
+    .chain(walls(false, sliding, fixed))
+    .chain([Event::Base, Event::Full])
+}
+
+fn exact_point(field: Field, (x, y): (i64, i64)) -> ExactPoint {
+  ExactPoint {
+    x: field.integer(x),
+    y: field.integer(y),
+  }
+}
+
+/// `R (p - b) + q`, where `R` is the rotation by the vector `(α, β) / √m`.
+fn rotate(
+  field: Field,
+  (alpha, beta): (i64, i64),
+  p: (i64, i64),
+  b: (i64, i64),
+  q: (i64, i64),
+) -> ExactPoint {
+  let (dx, dy) = (p.0 - b.0, p.1 - b.1);
+
+  ExactPoint {
+    x: &field.over_root(alpha * dx - beta * dy) + &field.integer(q.0),
+    y: &field.over_root(beta * dx + alpha * dy) + &field.integer(q.1),
+  }
+}
+
+/// End vertex of the edge `i` and the vector along it.
+fn edge(polygon: &[(i64, i64)], i: usize) -> ((i64, i64), (i64, i64)) {
+  let (start, end, _) = triple(polygon, i + 1);
+  (*end, (end.0 - start.0, end.1 - start.1))
+}
+
+/// The sliding polygon rotated so that its edge `i` is antiparallel to the edge `j` of
+/// the fixed polygon, with the end vertices of both edges together. The rotation is
+/// the integer vector `(α, β)` divided by `√m`, so every coordinate is `a + b·√m`.
+struct Contact {
+  /// Whether the sliding polygon was mirrored before being rotated.
+  reflected: bool,
+  field: Field,
+  rotation: (i64, i64),
+  sliding: Vec<ExactPoint>,
+  fixed: Vec<ExactPoint>,
+  direction: ExactPoint,
+  origin: ExactPoint,
+  /// Length of the sliding edge in fixed edges.
+  base: Quadratic,
+}
+
+impl Contact {
+  fn new(
+    sliding: &[(i64, i64)],
+    fixed: &[(i64, i64)],
+    (reflected, i, j): (bool, usize, usize),
+  ) -> Self {
+    let ((end, u), (to, v)) = (edge(sliding, i), edge(fixed, j));
+    let lengths = (u.0 * u.0 + u.1 * u.1, v.0 * v.0 + v.1 * v.1);
+    let field = Field::new(lengths.0 * lengths.1);
+    let rotation = (-(u.0 * v.0 + u.1 * v.1), -(u.0 * v.1 - u.1 * v.0));
+    let rotated = |p| rotate(field, rotation, p, end, to);
+
+    Self {
+      reflected,
+      field,
+      rotation,
+      sliding: sliding.iter().map(|s| rotated(*s)).collect(),
+      fixed: fixed.iter().map(|f| exact_point(field, *f)).collect(),
+      direction: exact_point(field, v),
+      origin: rotated((0, 0)),
+      base: &field.times_root(1) / &field.integer(lengths.1),
+    }
+  }
+
+  /// Shift `σ` at which `event` happens, where the sliding polygon is moved by `-σ`
+  /// times the fixed edge, if the contact edges still touch there.
+  fn shift(&self, event: Event) -> Option<Quadratic> {
+    let shift = match event {
+      Event::Wall(fixed_wall, wall, vertex) => self.wall_shift(fixed_wall, wall, vertex)?,
+      Event::Base => self.base.clone(),
+      Event::Full => self.field.integer(1),
+    };
+
+    (shift > self.field.integer(0) && shift <= &self.base + &self.field.integer(1)).then_some(shift)
+  }
+
+  /// Shift at which the vertex `vertex` reaches the supporting line of the edge `wall`
+  /// of the other polygon, a fixed edge when `fixed_wall`, or `None` when that edge is
+  /// parallel to the motion.
+  fn wall_shift(&self, fixed_wall: bool, wall: usize, vertex: usize) -> Option<Quadratic> {
+    let (walls, vertices, motion) = if fixed_wall {
+      let backwards = self.direction.scale(&-&self.field.integer(1));
+      (&self.fixed, &self.sliding, backwards)
+    } else {
+      (&self.sliding, &self.fixed, self.direction.clone())
+    };
+    let (_, p, q) = triple(walls, wall);
+    let wall = q.sub(p);
+    let speed = wall.cross(&motion);
+
+    (!speed.is_zero()).then(|| &(-&wall.cross(&vertices[vertex].sub(p))) / &speed)
+  }
+
+  fn placed(&self, shift: &Quadratic) -> Vec<ExactPoint> {
+    let moved = self.direction.scale(shift);
+    self.sliding.iter().map(|p| p.sub(&moved)).collect()
+  }
+
+  /// Placement of the sliding polygon at `placed`, moved by `shift`.
+  fn result(
+    &self,
+    placed: &[ExactPoint],
+    shift: &Quadratic,
+    (boundary, tol): (f64, &Tolerance),
+  ) -> MatchResult {
+    MatchResult {
+      polygon1: Polygon::new(placed.iter().map(ExactPoint::to_point).collect()),
+      polygon2: Polygon::new(self.fixed.iter().map(ExactPoint::to_point).collect()),
+      transform1: self.relative(shift),
+      transform2: Transform::default(),
+      boundary,
+      tolerance: *tol,
+      stats: SearchStats::default(),
+    }
+  }
+
+  /// Transform placing the first polygon, mirrored when `reflected`, moved by `shift`
+  /// against the fixed one, which stays in place.
+  fn relative(&self, shift: &Quadratic) -> Transform {
+    let (alpha, beta) = self.rotation;
+    let transform = Transform {
+      rotation: beta
+        .to_f64()
+        .unwrap_or(0_f64)
+        .atan2(alpha.to_f64().unwrap_or(1_f64)),
+      reflected: false,
+      translation: self.origin.sub(&self.direction.scale(shift)).to_point(),
+    };
+
+    if self.reflected {
+      mirror().then(&transform)
+    } else {
+      transform
+    }
+  }
+}
+
+/// A contact in floating point, to find the edges touching at a shift before
+/// measuring them exactly.
+struct Estimate {
+  sliding: Vec<Point>,
+  fixed: Vec<Point>,
+  direction: Point,
+  base: f64,
+  /// Placement of the sliding polygon before it is shifted.
+  placement: Transform,
+  /// Pairs of parallel edges, the only ones that can touch.
+  parallel: Vec<(usize, usize)>,
+}
+
+impl Estimate {
+  fn new(contact: &Contact) -> Self {
+    let points = |points: &[ExactPoint]| points.iter().map(ExactPoint::to_point).collect();
+
+    Self {
+      sliding: points(&contact.sliding),
+      fixed: points(&contact.fixed),
+      direction: contact.direction.to_point(),
+      base: contact.base.to_f64(),
+      placement: contact.relative(&contact.field.integer(0)),
+      parallel: parallel_edges(&contact.sliding, &contact.fixed),
+    }
+  }
+
+  /// Every event of the contact that may be within the contact edges, with the
+  /// estimate of the boundary and the shift at which it happens, from the longest
+  /// boundary to the shortest one. The shift is not a number when a vertex lies on the
+  /// supporting line of a wall almost parallel to the motion.
+  fn events(&self, tol: &Tolerance) -> Vec<(f64, f64, Event)> {
+    let (low, high) = (-self.margin(), self.base + 1_f64 + self.margin());
+    let mut shifts: Vec<(f64, Event)> = events(self.sliding.len(), self.fixed.len())
+      .map(|event| (self.shift(event), event))
+      // Comparisons with NaN are false, so those shifts are kept.
+      .filter(|(shift, _)| !(*shift < low || *shift > high))
+      .collect();
+    shifts.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut run = None;
+    let mut events: Vec<(f64, f64, Event)> = shifts
+      .into_iter()
+      .map(|(shift, event)| (self.estimate_in(&mut run, shift, tol), shift, event))
+      .collect();
+    events.sort_by(|a, b| b.0.total_cmp(&a.0));
+    events
+  }
+
+  /// Estimate of the boundary at `shift`, the one of the `run` of shifts it belongs
+  /// to when it is within the margin of its first shift. A shift that is not a number
+  /// may have any boundary.
+  fn estimate_in(&self, run: &mut Option<(f64, f64)>, shift: f64, tol: &Tolerance) -> f64 {
+    if shift.is_nan() {
+      return f64::INFINITY;
+    }
+    if run.is_none_or(|(first, _)| shift - first > self.margin()) {
+      *run = Some((shift, self.touching(shift, tol).0));
+    }
+    run.map_or(0_f64, |(_, approximate)| approximate)
+  }
+
+  /// Shift at which `event` happens, in floating point.
+  fn shift(&self, event: Event) -> f64 {
+    match event {
+      Event::Wall(fixed_wall, wall, vertex) => {
+        let (walls, vertices, motion) = if fixed_wall {
+          (&self.fixed, &self.sliding, self.direction.negate())
+        } else {
+          (&self.sliding, &self.fixed, self.direction)
+        };
+        let (_, p, q) = triple(walls, wall);
+        let wall = *q - *p;
+        -wall.cross(vertices[vertex] - *p) / wall.cross(motion)
+      }
+      Event::Base => self.base,
+      Event::Full => 1_f64,
+    }
+  }
+
+  /// Distance between shifts under which they are estimated alike.
+  fn margin(&self) -> f64 {
+    EPS * (self.base + 1_f64)
+  }
+
+  /// Placement of the sliding polygon moved by `shift`.
+  fn relative(&self, shift: f64) -> Transform {
+    self.placement.then(&Transform::translation(Point {
+      x: -self.direction.x * shift,
+      y: -self.direction.y * shift,
+    }))
+  }
+
+  /// Edge `i` of `points` moved by `-by` times the fixed edge.
+  fn moved_edge(&self, points: &[Point], i: usize, by: f64) -> Segment {
+    let (_, p, q) = triple(points, i);
+    let moved = |v: &Point| Point {
+      x: self.direction.x.mul_add(-by, v.x),
+      y: self.direction.y.mul_add(-by, v.y),
+    };
+    moved(p).seg(moved(q))
+  }
+
+  /// Common boundary at `shift` measured with the tolerance, like in the
+  /// floating-point search, and the pairs of edges sharing part of it. Every pair that
+  /// touches exactly is among them.
+  fn touching(&self, shift: f64, tol: &Tolerance) -> (f64, Vec<(usize, usize)>) {
+    let common: Vec<(f64, (usize, usize))> = self
+      .parallel
+      .iter()
+      .filter_map(|&(i, j)| {
+        let sliding = self.moved_edge(&self.sliding, i, shift);
+        let common = sliding.common_boundary_with(&self.moved_edge(&self.fixed, j, 0_f64), tol)?;
+        Some((common.length(), (i, j)))
+      })
+      .collect();
+
+    (
+      common.iter().map(|(length, _)| length).sum(),
+      common.into_iter().map(|(_, pair)| pair).collect(),
+    )
+  }
+}
+
+/// Shift of `contact` with the longest exact common boundary above `longer_than` where
+/// the polygons do not overlap, that boundary and the sliding polygon placed there.
+/// Every shift where a vertex reaches a wall is considered, from the longest estimate
+/// to the shortest one, until the estimate is more than `slack` below the longest
+/// boundary found. Placements already `measured` are skipped before their shift is
+/// computed exactly.
+fn longest_shift(
+  contact: &Contact,
+  (longer_than, slack): (f64, f64),
+  measured: &mut Measured,
+) -> Option<(f64, Quadratic, Vec<ExactPoint>)> {
+  let estimate = Estimate::new(contact);
+  let mut best: Option<(f64, Quadratic, Vec<ExactPoint>)> = None;
+
+  for (approximate, rough, event) in estimate.events(measured.tol) {
+    let longest = best.as_ref().map_or(longer_than, |b| b.0);
+    if approximate + slack <= longest {
+      break;
+    }
+
+    if rough.is_nan() || !measured.seen(&estimate.relative(rough)) {
+      best = measure(contact, &estimate, event, (longest, slack), measured).or(best);
+    }
+  }
+
+  best
+}
+
+/// Exact common boundary of `contact` at the shift of `event`, that shift and the
+/// sliding polygon placed there, when the boundary is longer than `longest` and the
+/// polygons do not overlap. Only the edges touching in the estimate are measured, and
+/// nothing is when the estimate at the exact shift cannot beat `longest` or the
+/// placement was already `measured`.
+fn measure(
+  contact: &Contact,
+  estimate: &Estimate,
+  event: Event,
+  (longest, slack): (f64, f64),
+  measured: &mut Measured,
+) -> Option<(f64, Quadratic, Vec<ExactPoint>)> {
+  let shift = contact.shift(event)?;
+  let (approximate, touching) = estimate.touching(shift.to_f64(), measured.tol);
+  let relative = estimate.relative(shift.to_f64());
+  if approximate + slack <= longest || measured.seen(&relative) {
+    return None;
+  }
+
+  measured.placements.push(relative);
+  let boundary = common_boundary(contact, &shift, &touching);
+  let placed = (boundary > longest).then(|| contact.placed(&shift))?;
+  (!overlaps(&placed, &contact.fixed)).then_some((boundary, shift, placed))
+}
+
+/// Placement of the pair of a candidate with the longest exact common boundary, or
+/// `None` when no shift gives a boundary longer than `longer_than` without overlapping.
+fn certify(
+  fixed: &[(i64, i64)],
+  candidate: &Candidate,
+  longer_than: f64,
+  measured: &mut Measured,
+) -> Option<MatchResult> {
+  let reflected = candidate.edges.0;
+  let source = if reflected {
+    mirror().apply_polygon(measured.polygon)
+  } else {
+    measured.polygon.clone()
+  };
+  let contact = Contact::new(&integer_vertices(&source)?, fixed, candidate.edges);
+  let (boundary, shift, placed) =
+    longest_shift(&contact, (longer_than, candidate.slack), measured)?;
+
+  Some(contact.result(&placed, &shift, (boundary, measured.tol)))
+}
+
+/// The first polygon moved to the right of the second one, without touching it.
+fn apart(polygon1: &Polygon, polygon2: &Polygon, tol: &Tolerance) -> MatchResult {
+  let right = |p: &Polygon, sign: f64| {
+    p.vertices
+      .iter()
+      .map(|v| sign * v.x)
+      .fold(f64::MIN, f64::max)
+  };
+  let transform1 = Transform::translation(Point {
+    x: right(polygon2, 1_f64) + right(polygon1, -1_f64) + 1_f64,
+    y: 0_f64,
+  });
+
+  MatchResult::relative(polygon1, polygon2, transform1, 0_f64, tol)
+}
+
+/// Placement found in floating point.
+struct Candidate {
+  /// Whether the sliding polygon is mirrored, and the sliding and fixed edges laid on
+  /// the X axis.
+  edges: (bool, usize, usize),
+  /// Longest boundary any shift of the pair may have once measured exactly: the one
+  /// found, plus what the tolerance may have missed.
+  bound: f64,
+  /// How far the estimate of a shift may fall below its exact boundary: a linear
+  /// tolerance for every pair of edges.
+  slack: f64,
+}
+
+impl Candidate {
+  fn new(
+    space: &SearchSpace,
+    slack: f64,
+    (pair, (boundary, _)): ((usize, usize), (f64, f64)),
+  ) -> Option<Self> {
+    let (reflected, edge) = space.sliding_edge(pair.0)?;
+
+    Some(Self {
+      edges: (reflected, edge, pair.1),
+      bound: boundary + space.slack,
+      slack,
+    })
+  }
+}
+
+/// Best placement of every pair found in floating point, from the longest boundary to
+/// the shortest one, the tolerance they were found with and the work done.
+fn candidates(
+  polygon1: &Polygon,
+  polygon2: &Polygon,
+  options: &MatchOptions,
+) -> Result<(Vec<Candidate>, Tolerance, SearchStats), PuzzleError> {
+  let (prepared1, prepared2) = prepare(polygon1, polygon2)?;
+  let space = SearchSpace::new(&prepared1, &prepared2, options);
+  let slack = space.tolerance.linear
+    * (polygon1.len() * polygon2.len())
+      .to_f64()
+      .unwrap_or(f64::INFINITY);
+  let candidates = ranked(&space, 1, options)
+    .into_iter()
+    .filter_map(|solution| Candidate::new(&space, slack, solution))
+    .collect();
+
+  Ok((candidates, space.tolerance, space.counters.total()))
+}
+
+/// Placements of the first polygon relative to the second one already measured.
+struct Measured<'a> {
+  polygon: &'a Polygon,
+  tol: &'a Tolerance,
+  placements: Vec<Transform>,
+}
+
+impl Measured<'_> {
+  /// Whether `relative` puts the polygon where one of the placements does.
+  fn seen(&self, relative: &Transform) -> bool {
+    self.placements.iter().any(|a| {
+      a.reflected == relative.reflected
+        && self.polygon.vertices[..2]
+          .iter()
+          .all(|p| self.tol.points_equal(a.apply(*p), relative.apply(*p)))
+    })
+  }
+}
+
+/// Certified placement with the longest exact boundary, the earliest of the
+/// `candidates` among equal ones. Candidates are measured in order until the longest
+/// boundary the next one may have is no longer than the best one measured. Each
+/// placement is measured once, even when several pairs of edges touch in it.
+fn longest(
+  polygon1: &Polygon,
+  fixed: &[(i64, i64)],
+  (candidates, tol): (&[Candidate], &Tolerance),
+) -> Option<MatchResult> {
+  let mut best: Option<MatchResult> = None;
+  let mut measured = Measured {
+    polygon: polygon1,
+    tol,
+    placements: vec![],
+  };
+
+  for candidate in candidates {
+    let longest = best.as_ref().map_or(0_f64, |b| b.boundary);
+    if best.is_some() && longest >= candidate.bound {
+      break;
+    }
+
+    best = certify(fixed, candidate, longest, &mut measured).or(best);
+  }
+
+  best
+}
+
+/// Best placement of two polygons with integer coordinates, certified with exact
+/// arithmetic.
+///
+/// The floating-point search ranks every pair of edges by its best boundary, without
+/// pruning any pair. The pairs are then rebuilt exactly from the longest boundary to
+/// the shortest one, where the rotation is an integer vector normalised only by a
+/// square root and the shifts are solved in `Q(√m)`. Every shift of a pair where a
+/// vertex reaches a wall is a candidate, and the boundaries of those a floating-point
+/// estimate cannot rule out are measured on the exact coordinates, so a shift
+/// misranked in floating point is still found. Measuring stops once no remaining pair
+/// can beat the best placement by more than the floating-point search may have
+/// missed, and the longest one that does not overlap is returned. Each placement is
+/// measured once, even when several pairs of edges touch in it. Only the final
+/// lengths are rounded. When none of them can be certified, the polygons are placed
+/// apart.
+///
+/// Returns `None` when a coordinate is not an integer or is larger than 16384 in
+/// absolute value, or when the rotations are restricted.
+///
+/// # Errors
+/// `DegeneratePolygon` when one of the polygons cannot be matched.
+pub fn best_match_exact(
+  polygon1: &Polygon,
+  polygon2: &Polygon,
+  options: &MatchOptions,
+) -> Result<Option<MatchResult>, PuzzleError> {
+  let (Some(fixed), Some(_), None) = (
+    integer_vertices(polygon2),
+    integer_vertices(polygon1),
+    options.rotations.angles(),
+  ) else {
+    return Ok(None);
+  };
+
+  let (candidates, tol, stats) = candidates(polygon1, polygon2, options)?;
+  let placement = longest(polygon1, &fixed, (&candidates, &tol))
+    .unwrap_or_else(|| apart(polygon1, polygon2, &tol));
+
+  Ok(Some(MatchResult { stats, ..placement }))
+}
@@ -0,0 +1,263 @@
+use std::{
+  cmp::Ordering,
+  ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+/// Number `a + b·√m` with rational `a` and `b`. Every number taking part in the same
+/// computation shares `m`. When `m` is a perfect square `b` is always zero.
+#[derive(Clone, Debug)]
+pub struct Quadratic {
+  a: BigRational,
+  b: BigRational,
+  m: i64,
+}
+
+/// The numbers `a + b·√m` for a fixed radicand.
+#[derive(Clone, Copy)]
+pub struct Field {
+  m: i64,
+  root: Option<i64>,
+}
+
+fn integer_root(m: i64) -> Option<i64> {
+  let r = m.isqrt();
+  (r * r == m).then_some(r)
+}
+
+impl Field {
+  /// # Panics
+  /// Panics if `m` is negative.
+  #[must_use]
+  pub fn new(m: i64) -> Self {
+    assert!(m >= 0, "the radicand should not be negative");
+    Self {
+      m,
+      root: integer_root(m),
+    }
+  }
+
+  #[must_use]
+  pub fn rational(&self, a: BigRational) -> Quadratic {
+    Quadratic {
+      a,
+      b: BigRational::zero(),
+      m: self.m,
+    }
+  }
+
+  #[must_use]
+  pub fn integer(&self, a: i64) -> Quadratic {
+    self.rational(BigRational::from_integer(BigInt::from(a)))
+  }
+
+  /// `a / √m`, which stays rational when `m` is a perfect square.
+  #[must_use]
+  pub fn over_root(&self, a: i64) -> Quadratic {
+    let a = BigRational::from_integer(BigInt::from(a));
+
+    match self.root {
+      Some(root) => self.rational(a / BigInt::from(root)),
+      None => Quadratic {
+        a: BigRational::zero(),
+        b: a / BigInt::from(self.m),
+        m: self.m,
+      },
+    }
+  }
+
+  /// `a · √m`.
+  #[must_use]
+  pub fn times_root(&self, a: i64) -> Quadratic {
+    let a = BigRational::from_integer(BigInt::from(a));
+
+    match self.root {
+      Some(root) => self.rational(a * BigInt::from(root)),
+      None => Quadratic {
+        a: BigRational::zero(),
+        b: a,
+        m: self.m,
+      },
+    }
+  }
+}
+
+impl Quadratic {
+  #[must_use]
+  pub fn sign(&self) -> Ordering {
+    let (sa, sb) = (
+      self.a.cmp(&BigRational::zero()),
+      self.b.cmp(&BigRational::zero()),
+    );
+
+    if sa == sb || sa.is_eq() || sb.is_eq() {
+      return sa.then(sb);
+    }
+
+    // Opposite signs: the larger of `a²` and `b²·m` wins.
+    if self.norm().is_positive() {
+      sa
+    } else {
+      sb
+    }
+  }
+
+  /// `max(self, 0)`.
+  #[must_use]
+  pub fn positive_part(&self) -> Self {
+    if self.sign() == Ordering::Less {
+      &self.clone() - self
+    } else {
+      self.clone()
+    }
+  }
+
+  #[must_use]
+  pub fn is_zero(&self) -> bool {
+    self.a.is_zero() && self.b.is_zero()
+  }
+
+  fn conjugate(&self) -> Self {
+    Self {
+      a: self.a.clone(),
+      b: -&self.b,
+      m: self.m,
+    }
+  }
+
+  /// `a² - b²·m`, which is only zero for zero since `b` is zero when `m` is a square.
+  fn norm(&self) -> BigRational {
+    &self.a * &self.a - &self.b * &self.b * BigInt::from(self.m)
+  }
+
+  /// Closest `f64`, computed through the conjugate when both terms cancel out.
+  #[must_use]
+  pub fn to_f64(&self) -> f64 {
+    let root = self.m.to_f64().unwrap_or(f64::NAN).sqrt();
+    let a = self.a.to_f64().unwrap_or(f64::NAN);
+    let b = self.b.to_f64().unwrap_or(f64::NAN);
+
+    if self.a.is_positive() == self.b.is_positive() || self.b.is_zero() {
+      b.mul_add(root, a)
+    } else {
+      self.norm().to_f64().unwrap_or(f64::NAN) / b.mul_add(-root, a)
+    }
+  }
+}
+
+impl PartialEq for Quadratic {
+  fn eq(&self, other: &Self) -> bool {
+    (self - other).is_zero()
+  }
+}
+
+impl PartialOrd for Quadratic {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some((self - other).sign())
+  }
+}
+
+impl Add for &Quadratic {
+  type Output = Quadratic;
+
+  fn add(self, rhs: Self) -> Quadratic {
+    Quadratic {
+      a: &self.a + &rhs.a,
+      b: &self.b + &rhs.b,
+      m: self.m,
+    }
+  }
+}
+
+impl Sub for &Quadratic {
+  type Output = Quadratic;
+
+  fn sub(self, rhs: Self) -> Quadratic {
+    Quadratic {
+      a: &self.a - &rhs.a,
+      b: &self.b - &rhs.b,
+      m: self.m,
+    }
+  }
+}
+
+impl Mul for &Quadratic {
+  type Output = Quadratic;
+
+  fn mul(self, rhs: Self) -> Quadratic {
+    let m = BigInt::from(self.m);
+
+    Quadratic {
+      a: &self.a * &rhs.a + &self.b * &rhs.b * m,
+      b: &self.a * &rhs.b + &self.b * &rhs.a,
+      m: self.m,
+    }
+  }
+}
+
+impl Div for &Quadratic {
+  type Output = Quadratic;
+
+  fn div(self, rhs: Self) -> Quadratic {
+    let norm = rhs.norm();
+    let product = self * &rhs.conjugate();
+
+    Quadratic {
+      a: product.a / &norm,
+      b: product.b / &norm,
+      m: self.m,
+    }
+  }
+}
+
+impl Neg for &Quadratic {
+  type Output = Quadratic;
+
+  fn neg(self) -> Quadratic {
+    Quadratic {
+      a: -&self.a,
+      b: -&self.b,
+      m: self.m,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_case::test_case;
+
+  #[test_case(2, 3, -2, Ordering::Greater ; "3 minus 2 root 2")]
+  #[test_case(2, 1, -1, Ordering::Less ; "1 minus root 2")]
+  #[test_case(4, 3, -2, Ordering::Less ; "3 minus 2 times 2")]
+  #[test_case(4, 4, -2, Ordering::Equal ; "4 minus 2 times 2")]
+  #[test_case(5, 0, 0, Ordering::Equal ; "zero")]
+  fn test_sign(m: i64, a: i64, b: i64, sign: Ordering) {
+    let field = Field::new(m);
+    let x = &field.integer(a) + &field.times_root(b);
+    assert_eq!(x.sign(), sign);
+  }
+
+  #[test]
+  fn test_field_operations() {
+    let field = Field::new(2);
+    let x = &field.integer(3) + &field.times_root(2);
+    let y = &field.integer(1) - &field.times_root(1);
+
+    assert!(&(&(&x * &y) / &y) - &x == field.integer(0));
+    assert!(&x / &x == field.integer(1));
+    assert!((x.to_f64() - 2_f64.sqrt().mul_add(2_f64, 3_f64)).abs() < 1e-12);
+    assert!(y < field.integer(0));
+  }
+
+  #[test]
+  fn test_to_f64_cancellation() {
+    let field = Field::new(2);
+    // 665857 - 470832·√2 ≈ 7.5e-7, with both terms around 6.6e5.
+    let x = &field.integer(665_857) - &field.times_root(470_832);
+    assert!((x.to_f64() - 7.509_119_2e-7).abs() < 1e-13);
+  }
+}
@@ -0,0 +1,37 @@
+use std::sync::{
+  atomic::{AtomicBool, Ordering},
+  Arc,
+};
+
+/// Flag that stops a search from another thread. Clones share the same flag.
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+  #[must_use]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Stops every search watching this token. The pairs being solved are finished
+  /// first, no new one is started.
+  pub fn cancel(&self) {
+    self.0.store(true, Ordering::Relaxed);
+  }
+
+  #[must_use]
+  pub fn is_cancelled(&self) -> bool {
+    self.0.load(Ordering::Relaxed)
+  }
+}
+
+/// Progress of a search, reported after each batch of pairs.
+#[derive(Clone, Copy, Debug)]
+pub struct Progress {
+  /// Pairs solved or skipped because their bound cannot beat `best`.
+  pub evaluated: usize,
+  /// Pairs whose edges can be made to touch.
+  pub total: usize,
+  /// Longest common boundary found so far.
+  pub best: f64,
+}
@@ -0,0 +1,103 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+use crate::{shapes::polygon::Polygon, tolerance::Tolerance};
+
+/// Orientations the first polygon may take relative to the second one.
+#[derive(Clone, Default)]
+pub enum RotationSet {
+  /// Any rotation, as in the ICPC problem.
+  #[default]
+  Any,
+  /// Both polygons keep their input orientation.
+  Fixed,
+  /// Multiples of 90 degrees.
+  QuarterTurns,
+  /// Counterclockwise angles in radians.
+  Angles(Vec<f64>),
+}
+
+impl RotationSet {
+  /// Allowed angles, or `None` when every rotation is allowed.
+  #[must_use]
+  pub fn angles(&self) -> Option<Vec<f64>> {
+    match self {
+      Self::Any => None,
+      Self::Fixed => Some(vec![0_f64]),
+      Self::QuarterTurns => Some((0..4).map(|k| f64::from(k) * PI / 2_f64).collect()),
+      Self::Angles(angles) => Some(angles.clone()),
+    }
+  }
+}
+
+/// Threads the search runs on.
+#[derive(Clone, Default)]
+pub enum Parallelism {
+  /// The global rayon pool.
+  #[default]
+  Global,
+  /// A single thread, in a pool of its own built once by `Parallelism::sequential`, so
+  /// that every search runs on it one pair after the other.
+  Sequential(Arc<ThreadPool>),
+  /// A pool managed by the caller.
+  Pool(Arc<ThreadPool>),
+}
+
+impl Parallelism {
+  /// # Errors
+  /// When the thread of the pool cannot be started.
+  pub fn sequential() -> Result<Self, ThreadPoolBuildError> {
+    Ok(Self::Sequential(Arc::new(
+      ThreadPoolBuilder::new().num_threads(1).build()?,
+    )))
+  }
+
+  /// Runs `op` on the selected threads, so that every parallel iterator it uses runs
+  /// there too.
+  pub fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+    match self {
+      Self::Global => op(),
+      Self::Pool(pool) | Self::Sequential(pool) => pool.install(op),
+    }
+  }
+}
+
+/// Settings for `best_match_with`. The default reproduces the ICPC problem, where
+/// pieces can be rotated but never turned over.
+#[derive(Clone, Default)]
+pub struct MatchOptions {
+  /// Also try the mirror image of the first polygon. Flipping only the second polygon
+  /// gives the mirror image of the same placements, so it is never tried separately.
+  pub allow_reflection: bool,
+  /// When restricted, the second polygon stays in its input position and the first
+  /// one is only rotated by the allowed angles.
+  pub rotations: RotationSet,
+  /// Tolerances for comparing lengths and shifts. When unset they are derived from
+  /// the bounding box of both polygons, so the answer does not depend on the units.
+  pub tolerance: Option<Tolerance>,
+  /// Evaluate orientation tests exactly. Slower, but overlap and contact tests always
+  /// agree on nearly collinear edges.
+  pub exact_predicates: bool,
+  /// Only try the contacts found by matching runs of equal edges, when any rotation is
+  /// allowed. Much faster on large polygons, but misses the placements whose longest
+  /// contact is not such a run. Falls back to the full search when there is none.
+  pub approximate: bool,
+  /// Threads the search runs on.
+  pub parallelism: Parallelism,
+}
+
+impl MatchOptions {
+  /// Tolerances to use for matching both polygons.
+  #[must_use]
+  pub fn tolerance_for(&self, polygon1: &Polygon, polygon2: &Polygon) -> Tolerance {
+    let tolerance = self
+      .tolerance
+      .unwrap_or_else(|| Tolerance::from_polygons(&[polygon1, polygon2]));
+
+    Tolerance {
+      exact: tolerance.exact || self.exact_predicates,
+      ..tolerance
+    }
+  }
+}
@@ -0,0 +1,155 @@
+use std::sync::OnceLock;
+
+use crate::{
+  error::PuzzleError,
+  shapes::polygon::{validation::Defect, Polygon},
+};
+
+use super::search_space::{mirrored_rotations, negated, rotations, Rotation};
+
+/// A polygon with the work `best_match_prepared` does on every input cached, so that
+/// comparing a piece against many others prepares it only once.
+///
+/// Each set of rotations is computed the first time it is needed, as a piece that is
+/// only ever fixed never needs its sliding rotations. A prepared polygon can be shared
+/// between threads.
+pub struct PreparedPolygon {
+  polygon: Polygon,
+  shortest_edge: f64,
+  /// Every edge laid on the X axis, used when the polygon stays fixed.
+  rotations: OnceLock<Vec<Rotation>>,
+  /// The same rotations turned by half a turn, used when the polygon slides.
+  sliding: OnceLock<Vec<Rotation>>,
+  /// Sliding rotations of the mirror image of the polygon.
+  mirrored: OnceLock<Vec<Rotation>>,
+}
+
+/// Why the polygon cannot be matched, if it cannot.
+fn degenerate(polygon: &Polygon) -> Option<String> {
+  if polygon.len() < 3 {
+    return Some(Defect::TooFewVertices(polygon.len()).to_string());
+  }
+
+  polygon
+    .vertices
+    .iter()
+    .position(|p| !p.x.is_finite() || !p.y.is_finite())
+    .map(|i| Defect::NotFinite(i).to_string())
+}
+
+impl TryFrom<Polygon> for PreparedPolygon {
+  type Error = PuzzleError;
+
+  fn try_from(polygon: Polygon) -> Result<Self, Self::Error> {
+    if let Some(reason) = degenerate(&polygon) {
+      return Err(PuzzleError::DegeneratePolygon(reason));
+    }
+
+    Ok(Self {
+      shortest_edge: polygon
+        .edges()
+        .map(|e| e.length())
+        .fold(f64::INFINITY, f64::min),
+      polygon,
+      rotations: OnceLock::new(),
+      sliding: OnceLock::new(),
+      mirrored: OnceLock::new(),
+    })
+  }
+}
+
+impl PreparedPolygon {
+  /// # Errors
+  /// `DegeneratePolygon` when the polygon has fewer than 3 vertices or a coordinate
+  /// that is not finite.
+  pub fn new(polygon: &Polygon) -> Result<Self, PuzzleError> {
+    Self::try_from(polygon.clone())
+  }
+
+  #[must_use]
+  pub const fn polygon(&self) -> &Polygon {
+    &self.polygon
+  }
+
+  pub(super) const fn shortest_edge(&self) -> f64 {
+    self.shortest_edge
+  }
+
+  pub(super) fn rotations(&self) -> &[Rotation] {
+    self.rotations.get_or_init(|| rotations(&self.polygon))
+  }
+
+  pub(super) fn sliding(&self) -> &[Rotation] {
+    self
+      .sliding
+      .get_or_init(|| self.rotations().iter().map(negated).collect())
+  }
+
+  /// Whether any set of rotations has been built.
+  #[cfg(test)]
+  pub(super) fn is_rotated(&self) -> bool {
+    self.rotations.get().is_some() || self.sliding.get().is_some() || self.mirrored.get().is_some()
+  }
+
+  pub(super) fn mirrored(&self) -> &[Rotation] {
+    self.mirrored.get_or_init(|| {
+      mirrored_rotations(&self.polygon)
+        .iter()
+        .map(negated)
+        .collect()
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    polygon_matcher::{best_match_prepared, options::MatchOptions},
+    shapes::point::Point,
+  };
+
+  fn polygon(vertices: &[(f64, f64)]) -> Polygon {
+    Polygon::new(vertices.iter().map(|&(x, y)| Point { x, y }).collect())
+  }
+
+  /// Addresses of the sets of rotations built so far.
+  fn built(prepared: &PreparedPolygon) -> [Option<*const Rotation>; 3] {
+    [&prepared.rotations, &prepared.sliding, &prepared.mirrored]
+      .map(|set| set.get().map(Vec::as_ptr))
+  }
+
+  /// Matches every polygon of the library against every one, itself included.
+  fn match_all(library: &[PreparedPolygon]) {
+    let options = MatchOptions {
+      allow_reflection: true,
+      ..MatchOptions::default()
+    };
+
+    for prepared1 in library {
+      for prepared2 in library {
+        best_match_prepared(prepared1, prepared2, &options).unwrap();
+      }
+    }
+  }
+
+  #[test]
+  fn test_rotations_built_once() {
+    let library: Vec<PreparedPolygon> = [
+      polygon(&[(0., 0.), (2., 0.), (2., 2.), (0., 2.)]),
+      polygon(&[(0., 0.), (3., 0.), (3., 1.), (1., 1.), (1., 3.), (0., 3.)]),
+      polygon(&[(0., 0.), (4., 0.), (1., 2.)]),
+    ]
+    .into_iter()
+    .map(PreparedPolygon::try_from)
+    .collect::<Result<_, _>>()
+    .unwrap();
+
+    match_all(&library);
+    let first: Vec<_> = library.iter().map(built).collect();
+    assert!(first.iter().flatten().all(Option::is_some));
+
+    match_all(&library);
+    assert_eq!(library.iter().map(built).collect::<Vec<_>>(), first);
+  }
+}
@@ -0,0 +1,395 @@
+use std::{borrow::Cow, f64::consts::SQRT_2};
+
+use num_traits::ToPrimitive;
+use rayon::prelude::*;
+
+use crate::{
+  shapes::{
+    edge_lines::EdgeLines, overlap_sweep::EdgeBoxes, point::Point, polygon::Polygon,
+    transform::Transform,
+  },
+  tolerance::Tolerance,
+};
+
+use super::{
+  bound::Directions,
+  options::MatchOptions,
+  prepared::PreparedPolygon,
+  stats::{Counters, SearchStats},
+  symmetry::period,
+  MatchResult,
+};
+
+/// A polygon placed so that one of its edges lies on the X axis, together with the
+/// transform that placed it, the length of that edge and its edges indexed by
+/// direction, by height and by supporting line.
+#[derive(Clone)]
+pub struct Rotation {
+  pub polygon: Polygon,
+  pub transform: Transform,
+  pub base: f64,
+  pub directions: Directions,
+  pub boxes: EdgeBoxes,
+  pub lines: EdgeLines,
+}
+
+impl Rotation {
+  /// Transform of the rotation followed by a move of `offset` units along the X axis.
+  fn shifted(&self, offset: f64) -> Transform {
+    self.transform.then(&Transform::translation(Point {
+      x: offset,
+      y: 0_f64,
+    }))
+  }
+
+  fn new(polygon: Polygon, transform: Transform, base: f64) -> Self {
+    Self {
+      directions: Directions::new(&polygon),
+      boxes: EdgeBoxes::new(&polygon),
+      lines: EdgeLines::new(&polygon),
+      polygon,
+      transform,
+      base,
+    }
+  }
+}
+
+enum Sliding<'a> {
+  /// Any rotation is allowed, so the sliding polygon is aligned by its own edges: the
+  /// first `period` of them, then as many edges of its mirror image when reflection is
+  /// allowed.
+  Aligned {
+    rotations: Vec<&'a Rotation>,
+    period: usize,
+  },
+  /// The orientation is fixed, so an edge can only be laid on the supporting line of
+  /// a fixed edge when both are antiparallel.
+  Oriented {
+    orientations: Vec<(Polygon, Transform)>,
+    edges: usize,
+  },
+}
+
+/// Every pair `(i, j)` to evaluate: the sliding polygon `i` and the fixed polygon `j`
+/// placed so that their contact edges lie on the X axis, the fixed one below it with
+/// the edge spanning `[0, base]` and the sliding one above it ending at the origin.
+pub struct SearchSpace<'a> {
+  sliding: Sliding<'a>,
+  fixed: &'a [Rotation],
+  pub tolerance: Tolerance,
+  /// Angle from antiparallel up to which edges are counted in the upper bound.
+  window: f64,
+  /// Added to every upper bound to cover contacts shorter than `SHARE`.
+  pub(super) slack: f64,
+  /// Work done on the pairs solved so far.
+  pub(super) counters: Counters,
+}
+
+/// The polygon placed so that its edge `i` lies on the X axis.
+pub fn rotation(polygon: &Polygon, i: usize) -> Rotation {
+  let transform = polygon.edge_alignment(i);
+  let rotated = transform.apply_polygon(polygon);
+  let base = rotated.vertices[i].dist(rotated.vertex_at((i + 1) as i32));
+
+  Rotation::new(rotated, transform, base)
+}
+
+pub fn rotations(polygon: &Polygon) -> Vec<Rotation> {
+  (0..polygon.len()).map(|i| rotation(polygon, i)).collect()
+}
+
+pub fn mirror() -> Transform {
+  Transform {
+    reflected: true,
+    ..Transform::default()
+  }
+}
+
+pub fn mirrored_rotations(polygon: &Polygon) -> Vec<Rotation> {
+  rotations(&mirror().apply_polygon(polygon))
+    .into_iter()
+    .map(|r| Rotation {
+      transform: mirror().then(&r.transform),
+      ..r
+    })
+    .collect()
+}
+
+pub fn negated(rotation: &Rotation) -> Rotation {
+  Rotation::new(
+    rotation.polygon.negate(),
+    rotation.transform.then(&Transform::half_turn()),
+    rotation.base,
+  )
+}
+
+/// Sliding rotations when any rotation is allowed. Rotations `period` edges apart lay
+/// the same points on the X axis, so only the first `period` of each set are kept.
+fn aligned<'a>(polygon: &'a PreparedPolygon, period: usize, options: &MatchOptions) -> Sliding<'a> {
+  let mirrored = if options.allow_reflection {
+    &polygon.mirrored()[..period]
+  } else {
+    &[]
+  };
+
+  Sliding::Aligned {
+    rotations: polygon.sliding()[..period].iter().chain(mirrored).collect(),
+    period,
+  }
+}
+
+fn oriented(polygon: &Polygon, angles: &[f64], options: &MatchOptions) -> Sliding<'static> {
+  let mut transforms: Vec<Transform> = angles.iter().map(|a| Transform::rotation(*a)).collect();
+
+  if options.allow_reflection {
+    let mirrored: Vec<Transform> = transforms.iter().map(|t| mirror().then(t)).collect();
+    transforms.extend(mirrored);
+  }
+
+  Sliding::Oriented {
+    orientations: transforms
+      .into_iter()
+      .map(|t| (t.apply_polygon(polygon), t))
+      .collect(),
+    edges: polygon.len(),
+  }
+}
+
+/// Length, in linear tolerances, from which a common segment constrains the angle between
+/// its edges. Shorter ones are covered by the slack.
+const SHARE: f64 = 1e3;
+
+/// Both ends of a common segment of length `l` lie within `reach` of each edge, either
+/// near one of its ends or within `area / length` of its line, so each edge turns at
+/// most `asin(2 reach / l)` away from the segment. Edges further from antiparallel than
+/// twice this share less than `SHARE` linear tolerances.
+fn antiparallel_window(shortest: f64, tol: &Tolerance) -> f64 {
+  let reach = (tol.area / shortest).max(SQRT_2 * tol.linear);
+  2_f64 * (2_f64 * reach / (SHARE * tol.linear)).min(1_f64).asin()
+}
+
+/// Pairs of edges that are not antiparallel may still share less than `SHARE` linear
+/// tolerances each.
+fn slack([polygon1, polygon2]: [&Polygon; 2], tol: &Tolerance) -> f64 {
+  SHARE
+    * tol.linear
+    * (polygon1.len() * polygon2.len())
+      .to_f64()
+      .unwrap_or(f64::INFINITY)
+}
+
+/// Sliding and fixed rotations to pair. Symmetries only remove pairs when any rotation
+/// is allowed, as an oriented polygon cannot be turned along with the fixed one.
+fn search_sets<'a>(
+  prepared1: &'a PreparedPolygon,
+  prepared2: &'a PreparedPolygon,
+  tol: &Tolerance,
+  options: &MatchOptions,
+) -> (Sliding<'a>, &'a [Rotation]) {
+  let (polygon1, polygon2) = (prepared1.polygon(), prepared2.polygon());
+
+  options.rotations.angles().map_or_else(
+    || {
+      (
+        aligned(prepared1, period(polygon1, tol), options),
+        &prepared2.rotations()[..period(polygon2, tol)],
+      )
+    },
+    |angles| (oriented(polygon1, &angles, options), prepared2.rotations()),
+  )
+}
+
+impl<'a> SearchSpace<'a> {
+  pub fn new(
+    prepared1: &'a PreparedPolygon,
+    prepared2: &'a PreparedPolygon,
+    options: &MatchOptions,
+  ) -> Self {
+    let (polygon1, polygon2) = (prepared1.polygon(), prepared2.polygon());
+    let tolerance = options.tolerance_for(polygon1, polygon2);
+    let shortest = prepared1.shortest_edge().min(prepared2.shortest_edge());
+    let (sliding, fixed) = search_sets(prepared1, prepared2, &tolerance, options);
+
+    Self {
+      sliding,
+      fixed,
+      window: antiparallel_window(shortest, &tolerance),
+      slack: slack([polygon1, polygon2], &tolerance),
+      tolerance,
+      counters: Counters::default(),
+    }
+  }
+
+  /// Longest common boundary the aligned pair may have under any shift.
+  pub fn upper_bound(&self, sliding: &Rotation, fixed: &Rotation) -> f64 {
+    sliding
+      .directions
+      .upper_bound(&fixed.directions, self.window)
+      + self.slack
+  }
+
+  /// Every pair `(i, j)`, in order, without collecting them first.
+  pub fn pairs(&self) -> impl IndexedParallelIterator<Item = (usize, usize)> {
+    let sliding_count = match &self.sliding {
+      Sliding::Aligned { rotations, .. } => rotations.len(),
+      Sliding::Oriented {
+        orientations,
+        edges,
+      } => orientations.len() * edges,
+    };
+    let fixed_count = self.fixed.len();
+
+    (0..sliding_count * fixed_count)
+      .into_par_iter()
+      .map(move |k| (k.div_euclid(fixed_count), k % fixed_count))
+  }
+
+  /// Both polygons of the pair, or `None` when their edges cannot be made to touch.
+  /// Aligned sliding polygons are borrowed from the prepared polygon.
+  pub fn alignment(&self, i: usize, j: usize) -> Option<(Cow<'_, Rotation>, &Rotation)> {
+    let fixed = &self.fixed[j];
+
+    let sliding = match &self.sliding {
+      Sliding::Aligned { rotations, .. } => Cow::Borrowed(rotations[i]),
+      Sliding::Oriented {
+        orientations,
+        edges,
+      } => {
+        let (polygon, transform) = &orientations[i / edges];
+        Cow::Owned(self.oriented_alignment(polygon, transform, i % edges, &fixed.transform)?)
+      }
+    };
+
+    Some((sliding, fixed))
+  }
+
+  /// Whether the sliding polygon of pair `i` is mirrored, and the edge of the polygon,
+  /// or of its mirror image, laid on the X axis. `None` when the orientation is fixed.
+  pub fn sliding_edge(&self, i: usize) -> Option<(bool, usize)> {
+    match &self.sliding {
+      Sliding::Aligned { period, .. } if i < *period => Some((false, i)),
+      Sliding::Aligned { period, .. } => Some((true, i - period)),
+      Sliding::Oriented { .. } => None,
+    }
+  }
+
+  fn oriented_alignment(
+    &self,
+    polygon: &Polygon,
+    transform: &Transform,
+    edge: usize,
+    frame: &Transform,
+  ) -> Option<Rotation> {
+    let p = frame.apply(polygon.vertices[edge]);
+    let q = frame.apply(polygon.vertex_at((edge + 1) as i32));
+
+    if !p.seg(q).is_horizontal(&self.tolerance) || q.x < p.x {
+      return None;
+    }
+
+    let to_origin = frame.then(&Transform::translation(q.negate()));
+    Some(Rotation::new(
+      to_origin.apply_polygon(polygon),
+      transform.then(&to_origin),
+      p.dist(q),
+    ))
+  }
+
+  /// Final placement of the pair after moving the sliding polygon `offset` units
+  /// along the X axis, or `None` when the pair is not in the search space.
+  pub fn placement(
+    &self,
+    (i, j): (usize, usize),
+    offset: f64,
+    boundary: f64,
+    (polygon1, polygon2): (&Polygon, &Polygon),
+  ) -> Option<MatchResult> {
+    let (sliding, fixed) = self.alignment(i, j)?;
+
+    let placed = match self.sliding {
+      Sliding::Aligned { .. } => side_by_side(&sliding, fixed, offset, (boundary, &self.tolerance)),
+      Sliding::Oriented { .. } => {
+        let transform1 = sliding.shifted(offset).then(&fixed.transform.inverse());
+        MatchResult::relative(polygon1, polygon2, transform1, boundary, &self.tolerance)
+      }
+    };
+
+    Some(MatchResult {
+      stats: self.counters.total(),
+      ..placed
+    })
+  }
+
+  /// Placement reported when no pair of edges can touch, or when no pair was solved:
+  /// the first pair, moved apart until the polygons do not meet.
+  pub fn detached(&self, (polygon1, polygon2): (&Polygon, &Polygon)) -> Option<MatchResult> {
+    match &self.sliding {
+      Sliding::Aligned { rotations, .. } => self.apart(rotations, (polygon1, polygon2)),
+      Sliding::Oriented { orientations, .. } => {
+        let (oriented, transform) = orientations.first()?;
+        Some(beside(
+          oriented,
+          transform,
+          (polygon1, polygon2),
+          &self.tolerance,
+        ))
+      }
+    }
+  }
+
+  /// The first aligned pair, with the sliding polygon moved right past the fixed one.
+  fn apart(&self, rotations: &[&Rotation], polygons: (&Polygon, &Polygon)) -> Option<MatchResult> {
+    let offset = self.fixed.first()?.base + span(&self.fixed[0].polygon).1
+      - span(&rotations.first()?.polygon).0;
+    self.placement((0, 0), offset, 0_f64, polygons)
+  }
+}
+
+/// Placement of the `sliding` rotation moved `offset` units along the X axis, against
+/// the `fixed` one.
+fn side_by_side(
+  sliding: &Rotation,
+  fixed: &Rotation,
+  offset: f64,
+  (boundary, tol): (f64, &Tolerance),
+) -> MatchResult {
+  let mut polygon1 = sliding.polygon.clone();
+  polygon1.vertices.iter_mut().for_each(|p| p.x += offset);
+
+  MatchResult {
+    polygon1,
+    polygon2: fixed.polygon.clone(),
+    transform1: sliding.shifted(offset),
+    transform2: fixed.transform,
+    boundary,
+    tolerance: *tol,
+    stats: SearchStats::default(),
+  }
+}
+
+/// Placement of `polygon1` turned by `transform` into `oriented`, then moved right
+/// past `polygon2`.
+fn beside(
+  oriented: &Polygon,
+  transform: &Transform,
+  (polygon1, polygon2): (&Polygon, &Polygon),
+  tol: &Tolerance,
+) -> MatchResult {
+  let (low, high) = span(polygon2);
+  let transform1 = transform.then(&Transform::translation(Point {
+    x: 2_f64.mul_add(high, -low) - span(oriented).0,
+    y: 0_f64,
+  }));
+
+  MatchResult::relative(polygon1, polygon2, transform1, 0_f64, tol)
+}
+
+/// Lowest and highest X coordinates of the polygon.
+fn span(polygon: &Polygon) -> (f64, f64) {
+  polygon
+    .vertices
+    .iter()
+    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+      (min.min(p.x), max.max(p.x))
+    })
+}
@@ -0,0 +1,86 @@
+use std::{
+  ops::AddAssign,
+  sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Work done by a search, to tell why a case is slow.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+  /// Pairs of rotations solved.
+  pub pairs: u64,
+  /// Candidate shifts collected for those pairs.
+  pub shifts: u64,
+  /// Candidate shifts dropped as too close to a previous one.
+  pub skipped_shifts: u64,
+  /// Shifts tested for an overlap.
+  pub overlap_tests: u64,
+  /// Shifts rejected because the polygons overlap.
+  pub overlaps: u64,
+  /// Common boundaries measured.
+  pub boundaries: u64,
+}
+
+impl SearchStats {
+  /// Work done on a single pair, where `collected` shifts were found, `tested` of them
+  /// were kept and tested for an overlap and `measured` of those did not overlap.
+  pub(super) const fn pair(collected: usize, tested: usize, measured: usize) -> Self {
+    Self {
+      pairs: 1,
+      shifts: collected as u64,
+      skipped_shifts: (collected - tested) as u64,
+      overlap_tests: tested as u64,
+      overlaps: (tested - measured) as u64,
+      boundaries: measured as u64,
+    }
+  }
+}
+
+impl AddAssign for SearchStats {
+  fn add_assign(&mut self, rhs: Self) {
+    self.pairs += rhs.pairs;
+    self.shifts += rhs.shifts;
+    self.skipped_shifts += rhs.skipped_shifts;
+    self.overlap_tests += rhs.overlap_tests;
+    self.overlaps += rhs.overlaps;
+    self.boundaries += rhs.boundaries;
+  }
+}
+
+/// Counters shared by the threads solving pairs, each adding the work of every pair.
+#[derive(Default)]
+pub(super) struct Counters {
+  pairs: AtomicU64,
+  shifts: AtomicU64,
+  skipped_shifts: AtomicU64,
+  overlap_tests: AtomicU64,
+  overlaps: AtomicU64,
+  boundaries: AtomicU64,
+}
+
+impl Counters {
+  pub(super) fn add(&self, stats: &SearchStats) {
+    self.pairs.fetch_add(stats.pairs, Ordering::Relaxed);
+    self.shifts.fetch_add(stats.shifts, Ordering::Relaxed);
+    self
+      .skipped_shifts
+      .fetch_add(stats.skipped_shifts, Ordering::Relaxed);
+    self
+      .overlap_tests
+      .fetch_add(stats.overlap_tests, Ordering::Relaxed);
+    self.overlaps.fetch_add(stats.overlaps, Ordering::Relaxed);
+    self
+      .boundaries
+      .fetch_add(stats.boundaries, Ordering::Relaxed);
+  }
+
+  pub(super) fn total(&self) -> SearchStats {
+    SearchStats {
+      pairs: self.pairs.load(Ordering::Relaxed),
+      shifts: self.shifts.load(Ordering::Relaxed),
+      skipped_shifts: self.skipped_shifts.load(Ordering::Relaxed),
+      overlap_tests: self.overlap_tests.load(Ordering::Relaxed),
+      overlaps: self.overlaps.load(Ordering::Relaxed),
+      boundaries: self.boundaries.load(Ordering::Relaxed),
+    }
+  }
+}
@@ -0,0 +1,205 @@
+use std::f64::consts::{PI, TAU};
+
+use crate::{
+  shapes::{polygon::Polygon, transform::Transform},
+  tolerance::Tolerance,
+  util::angle,
+};
+
+use super::MatchResult;
+
+/// Length of each edge and the angle it turns by into the next one.
+fn signature(polygon: &Polygon) -> Vec<(f64, f64)> {
+  let n = polygon.len() as i32;
+
+  (0..n)
+    .map(|i| {
+      let (a, b, c) = polygon.vertices_at(i + 1);
+      (a.dist(b), angle(b - a, c - b))
+    })
+    .collect()
+}
+
+/// Whether the signature is the same when started `shift` edges later. Only used to
+/// discard shifts quickly, so lengths and angles are compared loosely.
+fn repeats(signature: &[(f64, f64)], shift: usize, tol: &Tolerance) -> bool {
+  let shortest = signature
+    .iter()
+    .map(|(l, _)| *l)
+    .fold(f64::INFINITY, f64::min);
+  let turn = 4_f64 * tol.linear / shortest;
+
+  signature.iter().enumerate().all(|(i, (length, angle))| {
+    let (other_length, other_angle) = signature[(i + shift) % signature.len()];
+    let difference = (angle - other_angle + PI).rem_euclid(TAU) - PI;
+
+    (length - other_length).abs() <= 4_f64 * tol.linear && difference.abs() <= turn
+  })
+}
+
+/// Rotation and translation that lay edge `0` onto edge `shift`.
+fn motion(polygon: &Polygon, shift: usize) -> Transform {
+  let (p, q) = (
+    polygon.vertex_at(shift as i32),
+    polygon.vertex_at(shift as i32 + 1),
+  );
+  let rotation = angle(polygon.vertex_at(1) - polygon.vertex_at(0), q - p);
+
+  Transform {
+    rotation,
+    reflected: false,
+    translation: p - polygon.vertex_at(0).rot_ccw(rotation),
+  }
+}
+
+/// Whether the motion laying edge `0` onto edge `shift` maps every vertex onto the
+/// vertex `shift` places later.
+fn maps_onto_itself(polygon: &Polygon, shift: usize, tol: &Tolerance) -> bool {
+  let motion = motion(polygon, shift);
+
+  polygon
+    .vertices
+    .iter()
+    .enumerate()
+    .all(|(k, p)| tol.points_equal(motion.apply(*p), polygon.vertex_at((k + shift) as i32)))
+}
+
+/// Smallest number of edges the polygon can be turned by so that it lies on itself,
+/// which is its number of edges when it has no rotational symmetry.
+///
+/// Shifts of the sequence of edge lengths and turn angles that leave it unchanged are
+/// the only candidates, and each one is confirmed on the vertices themselves.
+#[must_use]
+pub fn period(polygon: &Polygon, tol: &Tolerance) -> usize {
+  let n = polygon.len();
+  let signature = signature(polygon);
+
+  (1..n)
+    .filter(|shift| n.is_multiple_of(*shift))
+    .find(|shift| repeats(&signature, *shift, tol) && maps_onto_itself(polygon, *shift, tol))
+    .unwrap_or(n)
+}
+
+/// Rotations and translations that lay the polygon onto itself, starting with the
+/// identity.
+#[must_use]
+pub fn symmetries(polygon: &Polygon, tol: &Tolerance) -> Vec<Transform> {
+  let period = period(polygon, tol);
+
+  (0..polygon.len())
+    .step_by(period.max(1))
+    .map(|shift| {
+      if shift == 0 {
+        Transform::default()
+      } else {
+        motion(polygon, shift)
+      }
+    })
+    .collect()
+}
+
+impl MatchResult {
+  /// Every placement equivalent to this one, where `polygon1` and `polygon2` are the
+  /// inputs it was computed for. Both polygons lie on the same points, but each
+  /// symmetry of an input gives a different transform.
+  ///
+  /// Only one placement per symmetry is evaluated by the matcher, so this expands the
+  /// result into the others. The placement itself comes first.
+  #[must_use]
+  pub fn symmetric_placements(
+    &self,
+    polygon1: &Polygon,
+    polygon2: &Polygon,
+    tol: &Tolerance,
+  ) -> Vec<Self> {
+    let symmetries2 = symmetries(polygon2, tol);
+
+    symmetries(polygon1, tol)
+      .iter()
+      .flat_map(|s1| symmetries2.iter().map(move |s2| (s1, s2)))
+      .map(|symmetries| self.after(symmetries, (polygon1, polygon2)))
+      .collect()
+  }
+
+  /// This placement of the inputs `polygon1` and `polygon2`, each moved by one of its
+  /// symmetries first.
+  fn after(
+    &self,
+    (s1, s2): (&Transform, &Transform),
+    (polygon1, polygon2): (&Polygon, &Polygon),
+  ) -> Self {
+    let (transform1, transform2) = (s1.then(&self.transform1), s2.then(&self.transform2));
+
+    Self {
+      polygon1: transform1.apply_polygon(polygon1),
+      polygon2: transform2.apply_polygon(polygon2),
+      transform1,
+      transform2,
+      boundary: self.boundary,
+      tolerance: self.tolerance,
+      stats: self.stats,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::shapes::point::Point;
+  use test_case::test_case;
+
+  fn regular(sides: u32, radius: f64) -> Polygon {
+    Polygon::new(
+      (0..sides)
+        .map(|k| {
+          let angle = TAU * f64::from(k) / f64::from(sides);
+          Point {
+            x: radius * angle.cos(),
+            y: radius * angle.sin(),
+          }
+        })
+        .collect(),
+    )
+  }
+
+  fn polygon(vertices: &[(f64, f64)]) -> Polygon {
+    Polygon::new(vertices.iter().map(|&(x, y)| Point { x, y }).collect())
+  }
+
+  #[test_case(3 ; "triangle")]
+  #[test_case(6 ; "hexagon")]
+  #[test_case(25 ; "twenty five sides")]
+  fn test_regular_period(sides: u32) {
+    assert_eq!(period(&regular(sides, 10_f64), &Tolerance::default()), 1);
+  }
+
+  #[test_case(&[(0_f64, 0_f64), (4_f64, 0_f64), (4_f64, 2_f64), (0_f64, 2_f64)], 2 ; "rectangle")]
+  #[test_case(&[(0_f64, 0_f64), (4_f64, 0_f64), (4_f64, 2_f64), (1_f64, 2_f64)], 4 ; "trapezoid")]
+  #[test_case(
+    &[
+      (0_f64, 0_f64), (2_f64, 0_f64), (2_f64, 1_f64), (3_f64, 1_f64), (3_f64, 3_f64),
+      (1_f64, 3_f64), (1_f64, 2_f64), (0_f64, 2_f64),
+    ],
+    4 ;
+    "two steps"
+  )]
+  fn test_period(vertices: &[(f64, f64)], expected: usize) {
+    assert_eq!(period(&polygon(vertices), &Tolerance::default()), expected);
+  }
+
+  #[test]
+  fn test_symmetries_map_onto_itself() {
+    let tol = Tolerance::default();
+    let polygon = regular(8, 5_f64);
+    let symmetries = symmetries(&polygon, &tol);
+    assert_eq!(symmetries.len(), 8);
+
+    for s in symmetries {
+      let image = s.apply_polygon(&polygon);
+      assert!(image
+        .vertices
+        .iter()
+        .all(|p| polygon.vertices.iter().any(|q| tol.points_equal(*p, *q))));
+    }
+  }
+}
@@ -0,0 +1,218 @@
+//! Adaptive-precision predicates in the style of Shewchuk's `orient2d`. The sign of
+//! a determinant is first estimated in `f64`, and only when the estimate is too close
+//! to the tolerance band to be trusted it is evaluated exactly as a floating-point
+//! expansion.
+
+use crate::shapes::point::Point;
+
+/// Relative error bound of `f64` arithmetic.
+const EPSILON: f64 = f64::EPSILON / 2_f64;
+
+/// Bound on the error of `left ± right` when each side is a product of differences,
+/// relative to `|left| + |right|`.
+const ERROR_BOUND: f64 = (3_f64 + 16_f64 * EPSILON) * EPSILON;
+
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+  let s = a + b;
+  let b_virtual = s - a;
+  let a_virtual = s - b_virtual;
+  (s, (a - a_virtual) + (b - b_virtual))
+}
+
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+  let p = a * b;
+  (p, a.mul_add(b, -p))
+}
+
+/// Adds `b` to a nonoverlapping expansion sorted by increasing magnitude, keeping it
+/// nonoverlapping and sorted, and dropping zero components.
+fn grow(expansion: &mut Vec<f64>, b: f64) {
+  let mut q = b;
+  let mut grown = Vec::with_capacity(expansion.len() + 1);
+
+  for e in expansion.iter() {
+    let (sum, error) = two_sum(q, *e);
+    q = sum;
+
+    if error != 0_f64 {
+      grown.push(error);
+    }
+  }
+
+  if q != 0_f64 {
+    grown.push(q);
+  }
+
+  *expansion = grown;
+}
+
+/// Exact sign of `offset + Σ a·b` over the `terms`.
+fn exact_sign(terms: &[(f64, f64)], offset: f64) -> i8 {
+  let mut expansion = vec![];
+
+  for (a, b) in terms {
+    let (p, e) = two_product(*a, *b);
+    grow(&mut expansion, e);
+    grow(&mut expansion, p);
+  }
+
+  grow(&mut expansion, offset);
+
+  // The most significant component is larger than the sum of all the others.
+  expansion
+    .last()
+    .map_or(0, |x| if *x > 0_f64 { 1 } else { -1 })
+}
+
+/// Whether an estimate with the given error bound is certainly above `band`, or
+/// certainly below it. `None` when it cannot be decided without exact arithmetic.
+fn compare_estimate(estimate: f64, error: f64, band: f64) -> Option<bool> {
+  let margin = 4_f64.mul_add(EPSILON * (estimate.abs() + band), error);
+
+  if estimate > band + margin {
+    Some(true)
+  } else if estimate + margin < band {
+    Some(false)
+  } else {
+    None
+  }
+}
+
+/// Products whose sum is `(a - o) × (b - o)`.
+const fn cross_terms(o: Point, a: Point, b: Point) -> [(f64, f64); 6] {
+  [
+    (a.x, b.y),
+    (-a.x, o.y),
+    (-o.x, b.y),
+    (-a.y, b.x),
+    (a.y, o.x),
+    (o.y, b.x),
+  ]
+}
+
+/// Products whose sum is `(a - o) · (b - o)`.
+const fn dot_terms(o: Point, a: Point, b: Point) -> [(f64, f64); 8] {
+  [
+    (a.x, b.x),
+    (-a.x, o.x),
+    (-o.x, b.x),
+    (o.x, o.x),
+    (a.y, b.y),
+    (-a.y, o.y),
+    (-o.y, b.y),
+    (o.y, o.y),
+  ]
+}
+
+/// Sign of `(a - o) × (b - o)`, with 0 when its magnitude is below `band`.
+pub fn orientation(o: Point, a: Point, b: Point, band: f64) -> i8 {
+  let left = (a.x - o.x) * (b.y - o.y);
+  let right = (a.y - o.y) * (b.x - o.x);
+  let error = ERROR_BOUND * (left.abs() + right.abs());
+
+  let outside = |sign: f64| {
+    compare_estimate(sign * (left - right), error, band).unwrap_or_else(|| {
+      let terms = cross_terms(o, a, b).map(|(x, y)| (sign * x, y));
+      exact_sign(&terms, -band) >= 0
+    })
+  };
+
+  i8::from(outside(1_f64)) - i8::from(outside(-1_f64))
+}
+
+/// Whether `(a - o) · (b - o)` is greater than `band`.
+pub fn dot_exceeds(o: Point, a: Point, b: Point, band: f64) -> bool {
+  let left = (a.x - o.x) * (b.x - o.x);
+  let right = (a.y - o.y) * (b.y - o.y);
+  let error = ERROR_BOUND * (left.abs() + right.abs());
+
+  compare_estimate(left + right, error, band)
+    .unwrap_or_else(|| exact_sign(&dot_terms(o, a, b), -band) > 0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::{thread_rng, Rng};
+
+  /// A point with coordinates up to `2^42` and its exact integer coordinates.
+  fn random_point(rng: &mut impl Rng) -> (Point, (i128, i128)) {
+    let mut coordinate = || {
+      let (hi, lo): (i32, i32) = (rng.gen_range(0..1 << 21), rng.gen_range(0..1 << 21));
+      (
+        f64::from(hi).mul_add(f64::from(1 << 21), f64::from(lo)),
+        (i128::from(hi) << 21) + i128::from(lo),
+      )
+    };
+
+    let ((x, exact_x), (y, exact_y)) = (coordinate(), coordinate());
+    (Point { x, y }, (exact_x, exact_y))
+  }
+
+  fn expected_orientation(det: i128, band: i128) -> i8 {
+    if det.abs() < band {
+      0
+    } else {
+      det.signum() as i8
+    }
+  }
+
+  /// Points `o` and `a`, and `b` one unit away from the line through them.
+  fn nearly_collinear(rng: &mut impl Rng) -> ([Point; 3], i8) {
+    let (o, (ox, oy)) = random_point(rng);
+    let (a, (ax, ay)) = random_point(rng);
+    let (ex, ey): (i32, i32) = (rng.gen_range(-1..=1), rng.gen_range(-1..=1));
+
+    let b = Point {
+      x: 2_f64.mul_add(a.x, -o.x) + f64::from(ex),
+      y: 2_f64.mul_add(a.y, -o.y) + f64::from(ey),
+    };
+    let (bx, by) = (2 * ax - ox + i128::from(ex), 2 * ay - oy + i128::from(ey));
+
+    let det = (ax - ox) * (by - oy) - (ay - oy) * (bx - ox);
+    ([o, a, b], expected_orientation(det, 1))
+  }
+
+  #[test]
+  fn test_orientation_nearly_collinear() {
+    let mut rng = thread_rng();
+
+    for _ in 0..100_000 {
+      let ([o, a, b], expected) = nearly_collinear(&mut rng);
+
+      assert_eq!(orientation(o, a, b, 1_f64), expected);
+      assert_eq!(orientation(b, o, a, 1_f64), expected);
+      assert_eq!(orientation(a, o, b, 1_f64), -expected);
+    }
+  }
+
+  #[test]
+  fn test_band_edges() {
+    let point = |x: f64, y: f64| Point { x, y };
+    let (big, twice) = (2_f64.powi(40), 2_f64.powi(41));
+    let (o, a) = (point(big, big), point(big + twice, big + twice));
+
+    for (b, expected) in [
+      (point(twice, twice + 1_f64), 0),
+      (point(twice, twice + 2_f64), 1),
+      (point(twice + 2_f64, twice), -1),
+    ] {
+      assert_eq!(orientation(o, a, b, twice + 1_f64), expected);
+      assert_eq!(orientation(a, b, o, twice + 1_f64), expected);
+    }
+
+    assert!(dot_exceeds(o, a, point(big + 1_f64, big), twice - 1_f64));
+    assert!(!dot_exceeds(o, a, point(big + 1_f64, big), twice));
+  }
+
+  #[test]
+  fn test_exact_sign() {
+    let big = 2_f64.powi(60);
+    assert_eq!(exact_sign(&[(big, big), (-big, big)], 1_f64), 1);
+    assert_eq!(exact_sign(&[(big, big), (-big, big)], -1_f64), -1);
+    assert_eq!(
+      exact_sign(&[(big + 1024_f64, big), (-big, big)], -big * 1024_f64),
+      0
+    );
+  }
+}
@@ -1,4 +1,4 @@
-use polygonal_puzzle::shapes::polygon::Polygon;
+use polygonal_puzzle::{error::PuzzleError, polygon_matcher::MatchResult};
 
 pub mod desmos;
 pub mod svg;
@@ -6,5 +6,16 @@ pub mod svg;
 const RESULTS_DIR: &str = "results";
 
 pub trait WriteResult {
-  fn write_result(&mut self, boundary: f64, case_number: i32, p1: Polygon, p2: Polygon);
+  /// `rank` is 1 for the best placement of the case and grows for the alternatives.
+  fn write_result(
+    &mut self,
+    case_number: i32,
+    rank: usize,
+    result: &MatchResult,
+  ) -> Result<(), PuzzleError>;
+
+  /// Writes whatever is still buffered, once every case is written.
+  fn finish(&mut self) -> Result<(), PuzzleError> {
+    Ok(())
+  }
 }
@@ -3,7 +3,7 @@ use std::{
   io::{BufWriter, Write},
 };
 
-use polygonal_puzzle::{shapes::polygon::Polygon, traits::desmos::Desmos, util::equal};
+use polygonal_puzzle::{error::PuzzleError, polygon_matcher::MatchResult, traits::desmos::Desmos};
 
 use super::{WriteResult, RESULTS_DIR};
 
@@ -12,38 +12,48 @@ pub struct OutputWriter {
 }
 
 impl OutputWriter {
-  pub fn new() -> Self {
-    let f = File::create(format!("{RESULTS_DIR}/desmos.txt")).unwrap();
+  pub fn new() -> Result<Self, PuzzleError> {
+    let f = File::create(format!("{RESULTS_DIR}/desmos.txt"))?;
 
-    Self {
+    Ok(Self {
       file_handler: BufWriter::new(f),
-    }
+    })
   }
 }
 
-impl Drop for OutputWriter {
-  fn drop(&mut self) {
-    self.file_handler.flush().expect("should be able to flush");
+impl OutputWriter {
+  fn label(case_number: i32, rank: usize) -> String {
+    if rank == 1 {
+      format!("case #{case_number}")
+    } else {
+      format!("case #{case_number}, placement {rank}")
+    }
   }
 }
 
 impl WriteResult for OutputWriter {
-  fn write_result(&mut self, boundary: f64, case_number: i32, p1: Polygon, p2: Polygon) {
-    let s = if equal(boundary, 0_f64) {
-      format!("(case #{case_number}) No solution found\n")
+  fn write_result(
+    &mut self,
+    case_number: i32,
+    rank: usize,
+    result: &MatchResult,
+  ) -> Result<(), PuzzleError> {
+    let label = Self::label(case_number, rank);
+
+    let s = if result.tolerance.zero(result.boundary) {
+      format!("({label}) No solution found\n")
     } else {
+      let (p1, p2) = (result.polygon1.fmt_desmos(), result.polygon2.fmt_desmos());
       format!(
-        "(case #{}) Solution found ({:.12})\n{}\n{}\n",
-        case_number,
-        boundary,
-        p1.fmt_desmos(),
-        p2.fmt_desmos()
+        "({label}) Solution found ({:.12})\n{p1}\n{p2}\n",
+        result.boundary
       )
     };
 
-    self
-      .file_handler
-      .write_all((s + "\n").as_bytes())
-      .expect("should be able to write to file");
+    Ok(self.file_handler.write_all((s + "\n").as_bytes())?)
+  }
+
+  fn finish(&mut self) -> Result<(), PuzzleError> {
+    Ok(self.file_handler.flush()?)
   }
 }
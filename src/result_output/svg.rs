@@ -1,9 +1,12 @@
 use self::shape_to_svg::ShapeToSvg;
 
 use super::{WriteResult, RESULTS_DIR};
+use polygonal_puzzle::error::PuzzleError;
+use polygonal_puzzle::polygon_matcher::MatchResult;
 use polygonal_puzzle::shapes::point::Point;
 use polygonal_puzzle::shapes::polygon::Polygon;
-use polygonal_puzzle::util::{cmp, equal, max, min};
+use polygonal_puzzle::tolerance::Tolerance;
+use polygonal_puzzle::util::{cmp, max, min};
 use svg::Document;
 
 const FACTOR: f64 = 20_f64;
@@ -35,7 +38,11 @@ impl OutputWriter {
     });
   }
 
-  fn render_polygons_image(mut p1: Polygon, mut p2: Polygon, path: &str) {
+  fn render_polygons_image(
+    (mut p1, mut p2): (Polygon, Polygon),
+    tol: &Tolerance,
+    path: &str,
+  ) -> Result<(), PuzzleError> {
     let (width, height) = Self::image_size_with_margin(&p1, &p2);
 
     Self::add_margin(&mut p1);
@@ -46,11 +53,11 @@ impl OutputWriter {
       .add(ShapeToSvg::polygon_to_svg_path(&p1, COLOR_POLYGON_1))
       .add(ShapeToSvg::polygon_to_svg_path(&p2, COLOR_POLYGON_2));
 
-    let svg_image = ShapeToSvg::boundary_to_svg_paths(&p1, &p2)
+    let svg_image = ShapeToSvg::boundary_to_svg_paths(&p1, &p2, tol)
       .into_iter()
       .fold(document_init, svg::node::element::SVG::add);
 
-    svg::save(path, &svg_image).unwrap();
+    Ok(svg::save(path, &svg_image)?)
   }
 
   fn scale_polygon(polygon: &mut Polygon) {
@@ -75,6 +82,14 @@ impl OutputWriter {
     iter.for_each(|p| *p -= min_point);
   }
 
+  fn image_path(case_number: i32, rank: usize) -> String {
+    if rank == 1 {
+      format!("{RESULTS_DIR}/{case_number:0>2}.svg")
+    } else {
+      format!("{RESULTS_DIR}/{case_number:0>2}_{rank}.svg")
+    }
+  }
+
   fn separate_polygons(p1: &Polygon, p2: &mut Polygon) {
     let shift_x = p1
       .vertices
@@ -88,11 +103,19 @@ impl OutputWriter {
 }
 
 impl WriteResult for OutputWriter {
-  fn write_result(&mut self, boundary: f64, case_number: i32, mut p1: Polygon, mut p2: Polygon) {
+  fn write_result(
+    &mut self,
+    case_number: i32,
+    rank: usize,
+    result: &MatchResult,
+  ) -> Result<(), PuzzleError> {
+    let mut p1 = result.polygon1.clone();
+    let mut p2 = result.polygon2.clone();
+
     Self::scale_polygon(&mut p1);
     Self::scale_polygon(&mut p2);
 
-    if equal(boundary, 0_f64) {
+    if result.tolerance.zero(result.boundary) {
       Self::move_polygons_corner(&mut [&mut p1]);
       Self::move_polygons_corner(&mut [&mut p2]);
       Self::separate_polygons(&p1, &mut p2);
@@ -100,6 +123,7 @@ impl WriteResult for OutputWriter {
       Self::move_polygons_corner(&mut [&mut p1, &mut p2]);
     }
 
-    Self::render_polygons_image(p1, p2, &format!("{RESULTS_DIR}/{case_number:0>2}.svg"));
+    let tol = result.tolerance.scaled(FACTOR);
+    Self::render_polygons_image((p1, p2), &tol, &Self::image_path(case_number, rank))
   }
 }
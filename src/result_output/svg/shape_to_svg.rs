@@ -1,6 +1,6 @@
 use polygonal_puzzle::{
-  shapes::{point::Point, polygon::Polygon, polyline_set::PolylineSet, segment::Segment},
-  traits::common_boundary::CommonBoundary,
+  shapes::{point::Point, polygon::Polygon, polyline_set::PolylineSet},
+  tolerance::Tolerance,
 };
 use svg::node::element::{path::Data, Path};
 
@@ -22,6 +22,15 @@ impl ShapeToSvg {
     }
   }
 
+  fn boundary_to_svg_data(polyline: &[Point]) -> Data {
+    match polyline.split_last() {
+      Some((last, rest)) if rest.len() > 1 && rest.first() == Some(last) => {
+        Self::polyline_to_svg_data(rest.iter()).close()
+      }
+      _ => Self::polyline_to_svg_data(polyline.iter()),
+    }
+  }
+
   pub fn polygon_to_svg_path(polygon: &Polygon, color: &str) -> Path {
     Path::new().set("fill", color).set(
       "d",
@@ -29,13 +38,13 @@ impl ShapeToSvg {
     )
   }
 
-  pub fn boundary_to_svg_paths(p1: &Polygon, p2: &Polygon) -> Vec<Path> {
-    let segments = <Polygon as CommonBoundary<Vec<Segment>>>::common_boundary(p1, p2);
+  pub fn boundary_to_svg_paths(p1: &Polygon, p2: &Polygon, tol: &Tolerance) -> Vec<Path> {
+    let segments = p1.common_segments_with(p2, tol);
 
-    PolylineSet::from_segments(&segments)
+    PolylineSet::from_segments(&segments, tol)
       .get_polylines()
-      .into_iter()
-      .map(|p| Self::polyline_to_svg_data(p.iter()))
+      .iter()
+      .map(|p| Self::boundary_to_svg_data(p))
       .map(|d| {
         Path::new()
           .set("stroke", COMMON_BOUNDARY_COLOR)
@@ -54,7 +63,7 @@ mod tests {
 
   #[test]
   fn test_polyline_to_svg_data() {
-    let points = vec![
+    let points = [
       Point { x: 0_f64, y: 0_f64 },
       Point { x: 1_f64, y: 1_f64 },
       Point { x: 4_f64, y: 5.2 },
@@ -66,4 +75,20 @@ mod tests {
     let path = Path::new().set("d", svg);
     assert_eq!(path.to_string(), "<path d=\"M0,0 L1,1 L4,5.2\"/>");
   }
+
+  #[test]
+  fn test_boundary_to_svg_data_closed() {
+    let points = [
+      Point { x: 0_f64, y: 0_f64 },
+      Point { x: 1_f64, y: 0_f64 },
+      Point { x: 1_f64, y: 1_f64 },
+      Point { x: 0_f64, y: 0_f64 },
+    ];
+
+    let path = Path::new().set("d", ShapeToSvg::boundary_to_svg_data(&points));
+    assert_eq!(path.to_string(), "<path d=\"M0,0 L1,0 L1,1 z\"/>");
+
+    let path = Path::new().set("d", ShapeToSvg::boundary_to_svg_data(&points[..3]));
+    assert_eq!(path.to_string(), "<path d=\"M0,0 L1,0 L1,1\"/>");
+  }
 }
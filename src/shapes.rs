@@ -1,4 +1,8 @@
+pub mod edge_lines;
+pub mod overlap_sweep;
 pub mod point;
+pub mod point_snapper;
 pub mod polygon;
 pub mod polyline_set;
 pub mod segment;
+pub mod transform;
@@ -0,0 +1,327 @@
+use std::{
+  f64::consts::{PI, SQRT_2},
+  sync::{Arc, OnceLock},
+};
+
+use crate::{tolerance::Tolerance, util::cmp};
+
+use super::{point::Point, polygon::Polygon, segment::Segment};
+
+/// Number of direction buckets over half a turn.
+const BUCKETS: u32 = 1024;
+
+/// Angle spanned by each direction bucket.
+const WIDTH: f64 = PI / BUCKETS as f64;
+
+/// Direction bucket of an edge. Antiparallel edges share the bucket.
+fn bucket(edge: &Segment) -> u32 {
+  let angle = (edge.q.y - edge.p.y)
+    .atan2(edge.q.x - edge.p.x)
+    .rem_euclid(PI);
+
+  (angle / WIDTH) as u32 % BUCKETS
+}
+
+/// Unit normal of the middle direction of each bucket.
+fn normals() -> &'static [Point] {
+  static NORMALS: OnceLock<Vec<Point>> = OnceLock::new();
+
+  NORMALS.get_or_init(|| {
+    (0..BUCKETS)
+      .map(|b| {
+        let angle = (f64::from(b) + 0.5) * WIDTH;
+        Point {
+          x: -angle.sin(),
+          y: angle.cos(),
+        }
+      })
+      .collect()
+  })
+}
+
+/// Offset of the line through the middle of `edge` along the normal of `bucket`.
+fn offset(edge: &Segment, bucket: u32) -> f64 {
+  let middle = Point {
+    x: f64::midpoint(edge.p.x, edge.q.x),
+    y: f64::midpoint(edge.p.y, edge.q.y),
+  };
+
+  normals()[bucket as usize] * middle
+}
+
+/// Offset and index of every edge, sorted by bucket and then by offset, with the
+/// position where each bucket starts.
+fn lines(edges: &[Segment]) -> (Vec<(f64, usize)>, Vec<usize>) {
+  let mut lines: Vec<(u32, f64, usize)> = edges
+    .iter()
+    .enumerate()
+    .map(|(i, e)| (bucket(e), offset(e, bucket(e)), i))
+    .collect();
+  lines.sort_by(|(a, x, _), (b, y, _)| a.cmp(b).then(cmp(x, y)));
+
+  let starts = (0..=BUCKETS)
+    .map(|b| lines.partition_point(|(k, _, _)| *k < b))
+    .collect();
+
+  (lines.into_iter().map(|(_, o, i)| (o, i)).collect(), starts)
+}
+
+fn ends(edges: &[Segment]) -> Vec<(Point, usize)> {
+  let mut ends: Vec<(Point, usize)> = edges
+    .iter()
+    .enumerate()
+    .flat_map(|(i, e)| [(e.p, i), (e.q, i)])
+    .collect();
+  ends.sort_by(|(a, _), (b, _)| cmp(&a.x, &b.x));
+  ends
+}
+
+fn lengths(edges: &[Segment]) -> Vec<(f64, usize)> {
+  let mut lengths: Vec<(f64, usize)> = edges
+    .iter()
+    .enumerate()
+    .map(|(i, e)| (e.length(), i))
+    .collect();
+  lengths.sort_by(|(a, _), (b, _)| cmp(a, b));
+  lengths
+}
+
+/// Edges of a polygon indexed by direction and supporting line, to find the edges
+/// that may share part of a given segment without comparing it with every edge.
+///
+/// Edges can only share a segment when they are almost collinear, or when they meet
+/// near an endpoint of both, so they are found by looking up the buckets around the
+/// direction of the segment and the endpoints around its own. The index does not
+/// depend on the tolerance, so it can be built once for every shift of the segments.
+#[derive(Clone)]
+pub struct EdgeLines {
+  /// Offset and index of each edge, sorted by bucket and then by offset.
+  lines: Arc<[(f64, usize)]>,
+  /// Position in `lines` where each bucket starts, followed by the number of edges.
+  starts: Arc<[usize]>,
+  /// Both endpoints of each edge, sorted by X coordinate.
+  ends: Arc<[(Point, usize)]>,
+  /// Length and index of each edge, from the shortest to the longest.
+  lengths: Arc<[(f64, usize)]>,
+  /// Largest absolute coordinate, which bounds the rounding error of the offsets.
+  magnitude: f64,
+}
+
+impl EdgeLines {
+  #[must_use]
+  pub fn new(polygon: &Polygon) -> Self {
+    let edges: Vec<Segment> = polygon.edges().collect();
+    let (lines, starts) = lines(&edges);
+
+    Self {
+      lines: lines.into(),
+      starts: starts.into(),
+      ends: ends(&edges).into(),
+      lengths: lengths(&edges).into(),
+      magnitude: polygon
+        .vertices
+        .iter()
+        .map(|p| p.x.abs().max(p.y.abs()))
+        .fold(0_f64, f64::max),
+    }
+  }
+
+  /// Lookups of the edges that may share part of a segment under the tolerance.
+  #[must_use]
+  pub fn query(&self, tol: &Tolerance) -> LineQuery<'_> {
+    let sine = (WIDTH / 2_f64).sin();
+    let radius = (2_f64 * tol.area / sine)
+      .sqrt()
+      .max(4_f64 * tol.linear / sine);
+
+    LineQuery {
+      lines: self,
+      radius: 2_f64 * radius,
+      distance: 2_f64 * (2_f64 * tol.linear).max(tol.area / radius),
+      short: self.lengths.partition_point(|(l, _)| *l <= 2_f64 * radius),
+      longest: self.lengths.last().map_or(0_f64, |(l, _)| *l),
+    }
+  }
+}
+
+/// Lookups in an `EdgeLines` index for a given tolerance.
+///
+/// A point an edge contains lies within `distance` of it, as long as the edge is longer
+/// than `radius`. Two such edges more than a bucket apart only share points within
+/// `radius` of an endpoint of each, and an edge shorter than `radius` may share points
+/// with any other edge.
+pub struct LineQuery<'a> {
+  lines: &'a EdgeLines,
+  radius: f64,
+  distance: f64,
+  /// Number of edges not longer than `radius`.
+  short: usize,
+  longest: f64,
+}
+
+impl LineQuery<'_> {
+  /// Fills `found` with the indices, in increasing order, of every edge that may share
+  /// part of `edge`. Any other edge has no common boundary with it.
+  pub fn candidates(&self, edge: &Segment, found: &mut Vec<usize>) {
+    // Cheaper than the length, which it exceeds by a factor of √2 at most.
+    let length = (edge.q.x - edge.p.x).abs() + (edge.q.y - edge.p.y).abs();
+    found.clear();
+
+    if length <= SQRT_2 * self.radius {
+      found.extend(0..self.lines.lines.len());
+      return;
+    }
+
+    found.extend(self.lines.lengths[..self.short].iter().map(|(_, i)| *i));
+    self.collinear(edge, self.window(edge, length), found);
+    self.corners(edge, found);
+
+    found.sort_unstable();
+    found.dedup();
+  }
+
+  /// Largest difference between the offsets of two edges in neighboring buckets that
+  /// share a point. Both edges may be slanted up to two buckets from the normal the
+  /// offsets are measured along, which moves their middle points off the common one.
+  fn window(&self, edge: &Segment, length: f64) -> f64 {
+    let magnitude = self.lines.magnitude + edge.p.x.abs().max(edge.p.y.abs()) + length;
+    let slant = 2_f64 * WIDTH * 4_f64.mul_add(self.distance, length + self.longest);
+    let rounding = 16_f64 * f64::EPSILON * magnitude;
+
+    3_f64.mul_add(self.distance, slant) + rounding
+  }
+
+  /// Edges in the buckets next to the one of `edge` whose supporting line is within
+  /// `window` of its own.
+  fn collinear(&self, edge: &Segment, window: f64, found: &mut Vec<usize>) {
+    let own = bucket(edge);
+
+    for b in [own + BUCKETS - 1, own, own + 1].map(|b| b % BUCKETS) {
+      let b = b as usize;
+      let lines = &self.lines.lines[self.lines.starts[b]..self.lines.starts[b + 1]];
+
+      if !lines.is_empty() {
+        let c = offset(edge, b as u32);
+        let start = lines.partition_point(|(o, _)| *o < c - window);
+        let end = lines.partition_point(|(o, _)| *o <= c + window);
+
+        found.extend(lines[start..end.max(start)].iter().map(|(_, i)| *i));
+      }
+    }
+  }
+
+  /// Edges with an endpoint within `radius` of an endpoint of `edge`.
+  fn corners(&self, edge: &Segment, found: &mut Vec<usize>) {
+    let (ends, radius) = (&self.lines.ends, self.radius);
+
+    for p in [edge.p, edge.q] {
+      let start = ends.partition_point(|(q, _)| q.x < p.x - radius);
+
+      found.extend(
+        ends[start..]
+          .iter()
+          .take_while(|(q, _)| q.x <= p.x + radius)
+          .filter(|(q, _)| (q.y - p.y).abs() <= radius)
+          .map(|(_, i)| *i),
+      );
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::{thread_rng, Rng};
+  use test_case::test_case;
+
+  fn polygon(vertices: &[(f64, f64)]) -> Polygon {
+    Polygon::new(vertices.iter().map(|&(x, y)| Point { x, y }).collect())
+  }
+
+  /// Staircase of `steps` unit steps, closed by a corner far below it.
+  fn staircase(steps: i32) -> Polygon {
+    let mut vertices = vec![(0_f64, -1_f64)];
+
+    for i in 0..steps {
+      let (x, y) = (f64::from(i), f64::from(i));
+      vertices.extend([(x + 1_f64, y), (x + 1_f64, y + 1_f64)]);
+    }
+
+    vertices.push((0_f64, f64::from(steps)));
+    polygon(&vertices)
+  }
+
+  fn brute_force(edge: &Segment, polygon: &Polygon, tol: &Tolerance) -> Vec<usize> {
+    polygon
+      .edges()
+      .enumerate()
+      .filter(|(_, e)| edge.common_boundary_with(e, tol).is_some())
+      .map(|(i, _)| i)
+      .collect()
+  }
+
+  fn assert_covers(polygon1: &Polygon, polygon2: &Polygon, tol: &Tolerance) {
+    let lines = EdgeLines::new(polygon2);
+    let mut found = vec![];
+
+    for edge in polygon1.edges() {
+      lines.query(tol).candidates(&edge, &mut found);
+
+      for i in brute_force(&edge, polygon2, tol) {
+        assert!(found.binary_search(&i).is_ok());
+      }
+    }
+  }
+
+  #[test_case(0_f64 ; "touching")]
+  #[test_case(1e-8 ; "within the tolerance")]
+  #[test_case(0.5 ; "apart")]
+  fn test_staircase_candidates(gap: f64) {
+    let polygon1 = staircase(20);
+    let polygon2 = Polygon::new(
+      polygon1
+        .vertices
+        .iter()
+        .map(|p| Point {
+          x: p.x + 1_f64 + gap,
+          y: p.y,
+        })
+        .collect(),
+    );
+
+    assert_covers(&polygon1, &polygon2, &Tolerance::default());
+  }
+
+  #[test]
+  fn test_few_candidates() {
+    let polygon = staircase(200);
+    let lines = EdgeLines::new(&polygon);
+    let mut found = vec![];
+
+    for edge in polygon.edges() {
+      lines
+        .query(&Tolerance::default())
+        .candidates(&edge, &mut found);
+      assert!(found.len() <= 8);
+    }
+  }
+
+  #[test]
+  fn test_random_candidates() {
+    let mut rng = thread_rng();
+    let tol = Tolerance::from_scale(1e3);
+
+    for _ in 0..200 {
+      let vertices: Vec<(f64, f64)> = (0..12)
+        .map(|_| rng.gen::<(u8, u8)>())
+        .map(|(x, y)| (f64::from(x % 6), f64::from(y % 6)))
+        .collect();
+      let mirrored: Vec<(f64, f64)> = vertices
+        .iter()
+        .map(|(x, y)| (*y + rng.gen_range(-1e-5..1e-5), *x))
+        .collect();
+
+      assert_covers(&polygon(&vertices), &polygon(&mirrored), &tol);
+    }
+  }
+}
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use crate::{tolerance::Tolerance, util::cmp};
+
+use super::{point::Point, polygon::Polygon};
+
+/// Bounding box of an edge.
+struct EdgeBox {
+  index: i32,
+  min: Point,
+  max: Point,
+}
+
+impl EdgeBox {
+  fn new(polygon: &Polygon, index: i32) -> Self {
+    let (p, q) = (polygon.vertex_at(index), polygon.vertex_at(index + 1));
+
+    Self {
+      index,
+      min: Point {
+        x: p.x.min(q.x),
+        y: p.y.min(q.y),
+      },
+      max: Point {
+        x: p.x.max(q.x),
+        y: p.y.max(q.y),
+      },
+    }
+  }
+}
+
+/// Bounding boxes of the edges of a polygon, sorted by their lowest Y coordinate.
+/// Moving the polygon along the X axis keeps them sorted, so they are computed once
+/// for every shift.
+#[derive(Clone)]
+pub struct EdgeBoxes {
+  boxes: Arc<[EdgeBox]>,
+  shortest: f64,
+}
+
+impl EdgeBoxes {
+  #[must_use]
+  pub fn new(polygon: &Polygon) -> Self {
+    let mut boxes: Vec<EdgeBox> = (0..polygon.len() as i32)
+      .map(|i| EdgeBox::new(polygon, i))
+      .collect();
+    boxes.sort_by(|a, b| cmp(&a.min.y, &b.min.y));
+
+    Self {
+      boxes: boxes.into(),
+      shortest: polygon
+        .edges()
+        .map(|e| e.length())
+        .fold(f64::INFINITY, f64::min),
+    }
+  }
+}
+
+/// Merges the boxes of both polygons by lowest Y coordinate, with 0 for the sliding
+/// polygon and 1 for the fixed one.
+fn merge<'a>(sliding: &'a [EdgeBox], fixed: &'a [EdgeBox]) -> Vec<(usize, &'a EdgeBox)> {
+  let mut merged = Vec::with_capacity(sliding.len() + fixed.len());
+  let (mut i, mut j) = (0, 0);
+
+  while i < sliding.len() || j < fixed.len() {
+    if j == fixed.len() || (i < sliding.len() && sliding[i].min.y <= fixed[j].min.y) {
+      merged.push((0, &sliding[i]));
+      i += 1;
+    } else {
+      merged.push((1, &fixed[j]));
+      j += 1;
+    }
+  }
+
+  merged
+}
+
+/// Vertices to pass to `corners_overlap` for an edge and an edge of the other
+/// polygon, the sliding one first.
+const fn pair(side: usize, edge: &EdgeBox, other: &EdgeBox) -> (i32, i32) {
+  if side == 0 {
+    (edge.index, other.index)
+  } else {
+    (other.index, edge.index)
+  }
+}
+
+/// Overlap test between a polygon sliding along the X axis and a fixed one, with the
+/// semantics of `Polygon::intersects_with`.
+///
+/// `corners_overlap` only reports pairs of edges whose boxes meet once widened by the
+/// distance up to which a point still lies on an edge. Every query sweeps the boxes of
+/// both polygons upwards, checking only the pairs of boxes that meet. The sliding
+/// polygon is never moved: its vertices are read with the offset of each query added.
+pub struct OverlapSweep<'a> {
+  fixed: &'a Polygon,
+  boxes: Vec<(usize, &'a EdgeBox)>,
+  /// Largest gap between two boxes that still meet once both are widened.
+  reach: f64,
+  tolerance: Tolerance,
+  /// Vertices of the last overlap found, which are checked first on the next query.
+  last: Option<(i32, i32)>,
+  /// Boxes of each polygon that may still meet the next ones, kept between queries
+  /// to reuse their buffers.
+  active: [Vec<&'a EdgeBox>; 2],
+}
+
+impl<'a> OverlapSweep<'a> {
+  #[must_use]
+  pub fn new(
+    sliding: &'a EdgeBoxes,
+    (fixed, fixed_boxes): (&'a Polygon, &'a EdgeBoxes),
+    tol: &Tolerance,
+  ) -> Self {
+    let shortest = sliding.shortest.min(fixed_boxes.shortest);
+
+    Self {
+      fixed,
+      boxes: merge(&sliding.boxes, &fixed_boxes.boxes),
+      reach: 2_f64 * tol.linear.max(tol.area / shortest),
+      tolerance: *tol,
+      last: None,
+      active: [vec![], vec![]],
+    }
+  }
+
+  /// Whether `sliding`, moved `offset` units along the X axis, overlaps the fixed
+  /// polygon.
+  pub fn overlaps(&mut self, sliding: &Polygon, offset: f64) -> bool {
+    if let Some(pair) = self.last {
+      if self.corners_overlap(sliding, offset, pair) {
+        return true;
+      }
+    }
+
+    self.last = self.sweep(sliding, offset);
+    self.last.is_some()
+  }
+
+  fn corners_overlap(&self, sliding: &Polygon, offset: f64, (i, j): (i32, i32)) -> bool {
+    let shift = Point {
+      x: offset,
+      y: 0_f64,
+    };
+    let (a0, a1, a2) = sliding.vertices_at(i);
+
+    Polygon::corners_overlap(
+      (a0 + shift, a1 + shift, a2 + shift),
+      self.fixed.vertices_at(j),
+      &self.tolerance,
+    )
+  }
+
+  /// Whether both widened boxes meet horizontally, where `a` belongs to `side` and the
+  /// sliding polygon is moved by `offset`.
+  fn meets(&self, (side, a): (usize, &EdgeBox), b: &EdgeBox, offset: f64) -> bool {
+    let offset = if side == 0 { offset } else { -offset };
+    a.min.x + offset <= b.max.x + self.reach && b.min.x <= a.max.x + offset + self.reach
+  }
+
+  fn sweep(&mut self, sliding: &Polygon, offset: f64) -> Option<(i32, i32)> {
+    let mut active = std::mem::take(&mut self.active);
+    active.iter_mut().for_each(Vec::clear);
+
+    let overlap = self.boxes.iter().find_map(|&(side, edge)| {
+      let other = &mut active[1 - side];
+      other.retain(|a| a.max.y + self.reach >= edge.min.y);
+
+      let overlap = other
+        .iter()
+        .filter(|a| self.meets((side, edge), a, offset))
+        .map(|a| pair(side, edge, a))
+        .find(|pair| self.corners_overlap(sliding, offset, *pair));
+
+      active[side].push(edge);
+      overlap
+    });
+
+    self.active = active;
+    overlap
+  }
+}
@@ -1,29 +1,38 @@
 use std::{
   fmt::Display,
-  ops::{Mul, Sub, SubAssign},
+  ops::{Add, AddAssign, Mul, Sub, SubAssign},
   str::FromStr,
 };
 
-use crate::util::equal;
+use crate::error::PuzzleError;
 
 use super::segment::Segment;
 
-#[derive(Clone, Copy, Debug)]
+/// Points compare equal only when their coordinates are. Compare them with
+/// `Tolerance::points_equal` to allow for rounding.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Point {
   pub x: f64,
   pub y: f64,
 }
 
-impl std::hash::Hash for Point {
-  fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-    let x = (self.x * 1000_f64).round() as i64;
-    let y = (self.y * 1000_f64).round() as i64;
-    x.hash(state);
-    y.hash(state);
+impl Add for Point {
+  type Output = Self;
+
+  fn add(self, rhs: Self) -> Self::Output {
+    Self {
+      x: self.x + rhs.x,
+      y: self.y + rhs.y,
+    }
   }
 }
 
-impl Eq for Point {}
+impl AddAssign for Point {
+  fn add_assign(&mut self, rhs: Self) {
+    self.x += rhs.x;
+    self.y += rhs.y;
+  }
+}
 
 impl SubAssign for Point {
   fn sub_assign(&mut self, rhs: Self) {
@@ -46,12 +55,6 @@ impl Mul for Point {
   }
 }
 
-impl PartialEq for Point {
-  fn eq(&self, other: &Self) -> bool {
-    equal(self.x, other.x) && equal(self.y, other.y)
-  }
-}
-
 impl Sub for Point {
   type Output = Self;
 
@@ -63,22 +66,34 @@ impl Sub for Point {
   }
 }
 
+/// Tokens of the line separated by single spaces, with the column each one starts at.
+fn tokens(s: &str) -> impl Iterator<Item = (usize, &str)> {
+  s.split(' ').scan(1, |column, token| {
+    let start = *column;
+    *column += token.len() + 1;
+    Some((start, token))
+  })
+}
+
+fn coordinate((column, token): (usize, &str)) -> Result<f64, PuzzleError> {
+  token
+    .parse()
+    .map_err(|e| PuzzleError::parse(1, column, format!("invalid coordinate {token:?}: {e}")))
+}
+
+/// Parses a line with both coordinates separated by a space. Errors are reported on
+/// line 1.
 impl FromStr for Point {
-  type Err = String;
+  type Err = PuzzleError;
 
   fn from_str(s: &str) -> Result<Self, Self::Err> {
-    let coordinates: Vec<f64> = s
-      .split(' ')
-      .map(str::parse)
-      .collect::<Result<Vec<f64>, std::num::ParseFloatError>>()
-      .map_err(|e| e.to_string())?;
-
-    let x = *coordinates.first().unwrap();
-    let y = *coordinates
-      .get(1)
-      .ok_or("point string should have Y value")?;
-
-    Ok(Self { x, y })
+    let coordinates: Vec<f64> = tokens(s).map(coordinate).collect::<Result<_, _>>()?;
+    let missing = || PuzzleError::parse(1, s.len() + 1, "point should have a Y value");
+
+    match coordinates[..] {
+      [x, y, ..] => Ok(Self { x, y }),
+      _ => Err(missing()),
+    }
   }
 }
 
@@ -118,28 +133,30 @@ impl Point {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::tolerance::Tolerance;
   use test_case::test_case;
 
-  #[test_case("4 5", Ok(Point { x: 4_f64, y: 5_f64 }))]
-  #[test_case("64.45 15.222", Ok(Point { x: 64.45, y: 15.222 }))]
-  #[test_case("-200.1 -100", Ok(Point { x: -200.1, y: -100_f64 }))]
-  #[test_case("64.45", Err("point string should have Y value".to_owned()))]
-  #[test_case("  ", Err("cannot parse float from empty string".to_owned()))]
-  #[test_case("44 x 66", Err("invalid float literal".to_owned()))]
-  #[test_case("xxx 543", Err("invalid float literal".to_owned()))]
-  #[test_case("543 xxx", Err("invalid float literal".to_owned()))]
-  fn test_from_str(s: &str, res: Result<Point, String>) {
-    assert_eq!(s.parse::<Point>(), res);
+  #[test_case("4 5", &Ok(Point { x: 4_f64, y: 5_f64 }))]
+  #[test_case("64.45 15.222", &Ok(Point { x: 64.45, y: 15.222 }))]
+  #[test_case("-200.1 -100", &Ok(Point { x: -200.1, y: -100_f64 }))]
+  #[test_case("64.45", &Err("line 1, column 6: point should have a Y value".to_owned()))]
+  #[test_case("", &Err("line 1, column 1: invalid coordinate \"\": cannot parse float from empty string".to_owned()) ; "empty")]
+  #[test_case("  ", &Err("line 1, column 1: invalid coordinate \"\": cannot parse float from empty string".to_owned()) ; "spaces")]
+  #[test_case("44 x 66", &Err("line 1, column 4: invalid coordinate \"x\": invalid float literal".to_owned()))]
+  #[test_case("xxx 543", &Err("line 1, column 1: invalid coordinate \"xxx\": invalid float literal".to_owned()))]
+  #[test_case("543 xxx", &Err("line 1, column 5: invalid coordinate \"xxx\": invalid float literal".to_owned()))]
+  fn test_from_str(s: &str, res: &Result<Point, String>) {
+    assert_eq!(&s.parse::<Point>().map_err(|e| e.to_string()), res);
   }
 
   #[test_case(Point { x: 5_f64, y: 6_f64 }, "(5.000000, 6.000000)")]
   #[test_case(Point { x: 1.23456, y: 6.54 }, "(1.234560, 6.540000)")]
   fn test_display(p: Point, res: &str) {
-    assert_eq!(format!("{}", p), res);
+    assert_eq!(format!("{p}"), res);
   }
 
   #[test_case(Point{ x: 10_f64, y: 0_f64 }, std::f64::consts::PI / 2_f64, Point{ x: 0_f64, y: 10_f64 })]
   fn test_rot_ccw(p: Point, ang: f64, res: Point) {
-    assert_eq!(p.rot_ccw(ang), res);
+    assert!(Tolerance::default().points_equal(p.rot_ccw(ang), res));
   }
 }
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use super::point::Point;
+
+/// Groups points that are within `tolerance` of each other on both axes, which is
+/// the same test `Point` equality does with its own tolerance.
+///
+/// Points are bucketed in a grid of cells several tolerances wide, so only the
+/// neighboring cells on the sides a point is close to need to be searched. Close
+/// points are merged with a union-find, so the grouping does not depend on where the
+/// cell borders fall.
+pub struct PointSnapper {
+  tolerance: f64,
+  points: Vec<Point>,
+  parent: Vec<usize>,
+  /// Last point inserted in each cell, the rest are chained through `next_in_cell`.
+  grid: HashMap<(i64, i64), usize>,
+  next_in_cell: Vec<Option<usize>>,
+}
+
+/// Cell width, in tolerances.
+const CELL_WIDTH: f64 = 32_f64;
+
+impl PointSnapper {
+  #[must_use]
+  pub fn new(tolerance: f64) -> Self {
+    Self {
+      tolerance: tolerance.max(f64::MIN_POSITIVE),
+      points: vec![],
+      parent: vec![],
+      grid: HashMap::new(),
+      next_in_cell: vec![],
+    }
+  }
+
+  /// Cell index along one axis and the neighboring cells that could hold a close
+  /// point. The margin is widened so rounding cannot hide a neighbor.
+  fn span(&self, v: f64) -> (i64, i64, i64) {
+    let scaled = v / (self.tolerance * CELL_WIDTH);
+    let cell = scaled.floor();
+    let margin = 1.5 / CELL_WIDTH;
+    let frac = scaled - cell;
+    let cell = cell as i64;
+
+    (
+      cell,
+      if frac < margin { cell - 1 } else { cell },
+      if 1_f64 - frac < margin {
+        cell + 1
+      } else {
+        cell
+      },
+    )
+  }
+
+  fn cell(&self, p: Point) -> (i64, i64) {
+    (self.span(p.x).0, self.span(p.y).0)
+  }
+
+  fn close(&self, a: Point, b: Point) -> bool {
+    (a.x - b.x).abs() < self.tolerance && (a.y - b.y).abs() < self.tolerance
+  }
+
+  /// Group representative, which is the first point inserted in the group.
+  #[must_use]
+  pub fn find(&self, mut idx: usize) -> usize {
+    while self.parent[idx] != idx {
+      idx = self.parent[idx];
+    }
+
+    idx
+  }
+
+  fn union(&mut self, a: usize, b: usize) {
+    let (a, b) = (self.find(a), self.find(b));
+    self.parent[a.max(b)] = a.min(b);
+  }
+
+  /// Merges the point at `idx` with the points already inserted that are close to it.
+  fn merge_neighbors(&mut self, idx: usize) {
+    let p = self.points[idx];
+    let ((_, x0, x1), (_, y0, y1)) = (self.span(p.x), self.span(p.y));
+
+    for x in x0..=x1 {
+      for y in y0..=y1 {
+        let mut next = self.grid.get(&(x, y)).copied();
+
+        while let Some(q) = next {
+          if self.close(p, self.points[q]) {
+            self.union(idx, q);
+          }
+
+          next = self.next_in_cell[q];
+        }
+      }
+    }
+  }
+
+  /// Adds a point and returns its index.
+  pub fn insert(&mut self, p: Point) -> usize {
+    let idx = self.points.len();
+
+    self.points.push(p);
+    self.parent.push(idx);
+    self.merge_neighbors(idx);
+
+    let cell = self.cell(p);
+    self.next_in_cell.push(self.grid.insert(cell, idx));
+    idx
+  }
+
+  #[must_use]
+  pub fn point(&self, idx: usize) -> Point {
+    self.points[idx]
+  }
+
+  #[must_use]
+  pub const fn len(&self) -> usize {
+    self.points.len()
+  }
+
+  #[must_use]
+  pub const fn is_empty(&self) -> bool {
+    self.points.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_case::test_case;
+
+  const fn point(x: f64, y: f64) -> Point {
+    Point { x, y }
+  }
+
+  #[test_case(
+    point(0.000_499_999_99, 0_f64),
+    point(0.000_500_000_01, 0_f64),
+    1e-7,
+    true
+  )]
+  #[test_case(point(1e6, 1e6), point(1e6 + 1e-8, 1e6 - 1e-8), 1e-7, true)]
+  #[test_case(point(0_f64, 0_f64), point(1e-6, 0_f64), 1e-7, false)]
+  #[test_case(point(0_f64, 0_f64), point(0.4, -0.4), 0.5, true)]
+  #[test_case(point(0_f64, 0_f64), point(0.4, -0.6), 0.5, false)]
+  fn test_snap_pair(a: Point, b: Point, tolerance: f64, same: bool) {
+    let mut snapper = PointSnapper::new(tolerance);
+    let (a, b) = (snapper.insert(a), snapper.insert(b));
+    assert_eq!(snapper.find(a) == snapper.find(b), same);
+  }
+
+  #[test]
+  fn test_snap_chain() {
+    let mut snapper = PointSnapper::new(1_f64);
+    let a = snapper.insert(point(0_f64, 0_f64));
+    let b = snapper.insert(point(2.7, 0_f64));
+    assert_ne!(snapper.find(a), snapper.find(b));
+
+    let c = snapper.insert(point(1.8, 0_f64));
+    let d = snapper.insert(point(0.9, 0_f64));
+
+    for idx in [a, b, c, d] {
+      assert_eq!(snapper.find(idx), a);
+    }
+  }
+}
@@ -1,66 +1,54 @@
 use crate::{
-  constants::EPS,
+  error::PuzzleError,
   iterators::{
     alternate_iterator::AlternateIterator, edge_iterator::EdgeIterator,
     vertex_iterator::VertexIterator,
   },
+  tolerance::Tolerance,
   traits::{
     common_boundary::CommonBoundary,
     desmos::Desmos,
     intersection::{Intersects, IntersectsHeuristic},
   },
-  util::{angle, ccw},
 };
 use std::{borrow::Borrow, str::FromStr};
 
-use super::{point::Point, segment::Segment};
+use super::{
+  edge_lines::EdgeLines,
+  overlap_sweep::{EdgeBoxes, OverlapSweep},
+  point::Point,
+  segment::Segment,
+  transform::Transform,
+};
+
+pub mod validation;
 
 #[derive(Clone)]
 pub struct Polygon {
   pub vertices: Vec<Point>,
 }
 
+impl Intersects for Polygon {
+  fn intersects(&self, other: &Self) -> bool {
+    self.overlaps_with(other, &Tolerance::default())
+  }
+}
+
 impl IntersectsHeuristic for Polygon {
   fn intersects(&self, other: &Self, prev: &mut (i32, i32)) -> bool {
-    for i in AlternateIterator::new().take(self.len()) {
-      for j in AlternateIterator::new().take(other.len()) {
-        if Self::intersection_aux(self, other, prev.0 + i, prev.1 + j) {
-          prev.0 += i;
-          prev.1 += j;
-          return true;
-        }
-      }
-    }
-
-    false
+    self.intersects_with(other, prev, &Tolerance::default())
   }
 }
 
 impl CommonBoundary<f64> for Polygon {
   fn common_boundary(&self, other: &Self) -> f64 {
-    self
-      .edges()
-      .flat_map(|e1: Segment| {
-        other
-          .edges()
-          .map(move |e2: Segment| e1.common_boundary(&e2))
-          .map(|s| s.map_or(0_f64, |s| s.length()))
-      })
-      .sum()
+    self.common_boundary_with(other, &Tolerance::default())
   }
 }
 
 impl CommonBoundary<Vec<Segment>> for Polygon {
   fn common_boundary(&self, other: &Self) -> Vec<Segment> {
-    self
-      .edges()
-      .flat_map(|e1| {
-        other
-          .edges()
-          .filter_map(move |e2: Segment| e1.common_boundary(&e2))
-          .filter(|s| s.length() > EPS)
-      })
-      .collect()
+    self.common_segments_with(other, &Tolerance::default())
   }
 }
 
@@ -77,18 +65,35 @@ impl Desmos for Polygon {
   }
 }
 
+/// Error of a polygon whose input ends after `found` of its `expected` vertices.
+fn missing_vertices(expected: usize, found: usize) -> PuzzleError {
+  PuzzleError::parse(
+    found + 1,
+    1,
+    format!("expected {expected} vertices, found {found}"),
+  )
+}
+
 impl Polygon {
+  /// Reads `vertices_count` vertices, one per line.
+  ///
   /// # Errors
-  /// Parsing errors may occur.
-  pub fn from<I>(vertices_count: usize, lines: &mut I) -> Result<Self, String>
+  /// A vertex that cannot be parsed, or missing lines. Lines are numbered from the
+  /// first one read.
+  pub fn from<I>(vertices_count: usize, lines: &mut I) -> Result<Self, PuzzleError>
   where
     I: Iterator,
     I::Item: Borrow<str>,
   {
     let mut vertices: Vec<Point> = lines
       .take(vertices_count)
-      .map(|line| Point::from_str(line.borrow()))
-      .collect::<Result<Vec<Point>, String>>()?;
+      .enumerate()
+      .map(|(i, line)| Point::from_str(line.borrow()).map_err(|e| e.below(i)))
+      .collect::<Result<_, _>>()?;
+
+    if vertices.len() < vertices_count {
+      return Err(missing_vertices(vertices_count, vertices.len()));
+    }
 
     vertices.reverse();
 
@@ -96,27 +101,27 @@ impl Polygon {
   }
 
   #[must_use]
-  pub fn new(vertices: Vec<Point>) -> Self {
+  pub const fn new(vertices: Vec<Point>) -> Self {
     Self { vertices }
   }
 
   #[must_use]
-  pub const fn edges(&self) -> EdgeIterator {
+  pub const fn edges(&self) -> EdgeIterator<'_> {
     EdgeIterator::new(self)
   }
 
   #[must_use]
-  pub const fn vertices(&self) -> VertexIterator {
+  pub const fn vertices(&self) -> VertexIterator<'_> {
     VertexIterator::new(self)
   }
 
   #[must_use]
-  pub fn len(&self) -> usize {
+  pub const fn len(&self) -> usize {
     self.vertices.len()
   }
 
   #[must_use]
-  pub fn is_empty(&self) -> bool {
+  pub const fn is_empty(&self) -> bool {
     self.vertices.is_empty()
   }
 
@@ -145,65 +150,236 @@ impl Polygon {
     }
   }
 
+  /// Transform that lays the edge starting at vertex `i` on the X axis, with its
+  /// end vertex at the origin and its start vertex on the positive side.
+  #[must_use]
+  pub fn edge_alignment(&self, i: usize) -> Transform {
+    let p = self.vertices[i];
+    let q = self.vertex_at((i + 1) as i32) - p;
+    let ang = q.y.atan2(-q.x);
+
+    Transform {
+      rotation: ang,
+      reflected: false,
+      translation: self.vertex_at((i + 1) as i32).rot_ccw(ang).negate(),
+    }
+  }
+
+  #[must_use]
+  pub fn edge_alignments(&self) -> Vec<Transform> {
+    (0..self.len()).map(|i| self.edge_alignment(i)).collect()
+  }
+
   #[must_use]
-  #[allow(clippy::too_many_lines)]
   pub fn rotations(&self) -> Vec<Self> {
-    let polygon = &mut self.clone();
-    let mut polygons = vec![];
+    self
+      .edge_alignments()
+      .iter()
+      .map(|t| t.apply_polygon(self))
+      .collect()
+  }
 
-    for i in 0..polygon.len() {
-      let p = polygon.vertices[i];
+  /// Whether the interiors of both polygons overlap. The search starts around the
+  /// pair of vertices in `prev`, which is updated to the overlapping pair found.
+  pub fn intersects_with(&self, other: &Self, prev: &mut (i32, i32), tol: &Tolerance) -> bool {
+    for i in AlternateIterator::new().take(self.len()) {
+      for j in AlternateIterator::new().take(other.len()) {
+        if Self::intersection_aux(self, other, prev.0 + i, prev.1 + j, tol) {
+          prev.0 += i;
+          prev.1 += j;
+          return true;
+        }
+      }
+    }
 
-      polygon.vertices.iter_mut().for_each(|point| *point -= p);
+    false
+  }
 
-      let q = polygon.vertex_at((i + 1) as i32);
-      let ang = q.y.atan2(-q.x);
+  /// Whether the interiors of both polygons overlap, with the same semantics as
+  /// `intersects_with` but without scanning every pair of vertices.
+  #[must_use]
+  pub fn overlaps_with(&self, other: &Self, tol: &Tolerance) -> bool {
+    let (boxes1, boxes2) = (EdgeBoxes::new(self), EdgeBoxes::new(other));
+    OverlapSweep::new(&boxes1, (other, &boxes2), tol).overlaps(self, 0_f64)
+  }
 
-      polygon
-        .vertices
-        .iter_mut()
-        .for_each(|p| *p = p.rot_ccw(ang));
+  #[must_use]
+  pub fn edge(&self, i: usize) -> Segment {
+    Segment {
+      p: self.vertices[i],
+      q: self.vertex_at(i as i32 + 1),
+    }
+  }
 
-      let new_q = polygon.vertex_at((i + 1) as i32);
+  #[must_use]
+  pub fn common_boundary_with(&self, other: &Self, tol: &Tolerance) -> f64 {
+    self.common_boundary_at(0_f64, (other, &EdgeLines::new(other)), tol, &mut vec![])
+  }
 
-      polygon.vertices.iter_mut().for_each(|p| *p -= new_q);
+  /// Common boundary with `other` once this polygon is moved `offset` units along the
+  /// X axis, where `lines` indexes the edges of `other` and `found` is a buffer that
+  /// can be reused between calls. Same sum as comparing every pair of edges, since the
+  /// pairs skipped share nothing.
+  pub fn common_boundary_at(
+    &self,
+    offset: f64,
+    (other, lines): (&Self, &EdgeLines),
+    tol: &Tolerance,
+    found: &mut Vec<usize>,
+  ) -> f64 {
+    let mut total = 0_f64;
+    self.for_each_common(offset, (other, lines), tol, found, |s| {
+      total += s.length();
+    });
+    total
+  }
 
-      polygons.push(polygon.clone());
+  #[must_use]
+  pub fn common_segments_with(&self, other: &Self, tol: &Tolerance) -> Vec<Segment> {
+    let mut segments = vec![];
+    let lines = EdgeLines::new(other);
+    self.for_each_common(0_f64, (other, &lines), tol, &mut vec![], |s| {
+      if s.length() > tol.linear {
+        segments.push(s);
+      }
+    });
+    segments
+  }
+
+  /// Calls `f` with the common boundary of every pair of edges that share part of
+  /// their length, in the order of the edges of this polygon and then of `other`.
+  fn for_each_common(
+    &self,
+    offset: f64,
+    (other, lines): (&Self, &EdgeLines),
+    tol: &Tolerance,
+    found: &mut Vec<usize>,
+    mut f: impl FnMut(Segment),
+  ) {
+    let query = lines.query(tol);
+
+    for e1 in self.edges().map(|e| e.shifted(offset)) {
+      query.candidates(&e1, found);
+
+      found
+        .iter()
+        .filter_map(|j| e1.common_boundary_with(&other.edge(*j), tol))
+        .for_each(&mut f);
     }
+  }
 
-    polygons
+  fn intersection_aux(p1: &Self, p2: &Self, i: i32, j: i32, tol: &Tolerance) -> bool {
+    Self::corners_overlap(p1.vertices_at(i), p2.vertices_at(j), tol)
   }
 
-  #[allow(clippy::too_many_lines)]
-  fn intersection_aux(p1: &Self, p2: &Self, i: i32, j: i32) -> bool {
-    let (a0, a1, a2) = p1.vertices_at(i);
-    let (b0, b1, b2) = p2.vertices_at(j);
+  /// Whether the interiors near the corner `a1` of one polygon, between its edges from
+  /// `a0` and to `a2`, and near the corner `b1` of the other one overlap.
+  pub(crate) fn corners_overlap(
+    a: (Point, Point, Point),
+    b: (Point, Point, Point),
+    tol: &Tolerance,
+  ) -> bool {
+    let ((_, a1, a2), (_, b1, b2)) = (a, b);
+
+    a1.seg(a2).intersects_with(&b1.seg(b2), tol)
+      || corner_on_edge(a, b, tol)
+      || corner_on_edge(b, a, tol)
+      || (tol.points_equal(a1, b1) && edge_inside_corner(a, b, tol))
+  }
+}
 
-    if a1.seg(a2).intersects(&b1.seg(b2)) {
-      return true;
-    }
+/// Whether the corner `a1` lies inside the edge from `b1` to `b2`, with one of its
+/// edges going to the left of that edge.
+fn corner_on_edge(
+  (a0, a1, a2): (Point, Point, Point),
+  (_, b1, b2): (Point, Point, Point),
+  tol: &Tolerance,
+) -> bool {
+  b1.seg(b2).contains_except_endpoints(a1, tol) && (tol.ccw(b1, b2, a2) || tol.ccw(b1, b2, a0))
+}
 
-    if b1.seg(b2).contains_except_endpoints(a1) && (ccw(b1, b2, a2) || ccw(b1, b2, a0)) {
-      return true;
-    }
-    if a1.seg(a2).contains_except_endpoints(b1) && (ccw(a1, a2, b2) || ccw(a1, a2, b0)) {
-      return true;
-    }
+/// Whether the direction from `b1` to `v` lies strictly inside the corner `b1`,
+/// turning counterclockwise from its edge to `b2` to its edge from `b0`.
+fn inside_corner((b0, b1, b2): (Point, Point, Point), v: Point, tol: &Tolerance) -> bool {
+  let (after, before) = (tol.ccw(b1, b2, v), tol.ccw(b1, v, b0));
 
-    if a1 == b1 {
-      let th = angle(b2 - b1, b0 - b1);
+  if tol.orientation(b1, b2, b0) >= 0 {
+    after && before
+  } else {
+    after || before
+  }
+}
 
-      let th2 = angle(b2 - b1, a0 - b1);
-      if th2 > EPS && th2 < th - EPS {
-        return true;
-      }
+/// Whether the direction from `b1` to `v` runs along the edge from `b1` to `b2`.
+fn along_edge(b1: Point, b2: Point, v: Point, tol: &Tolerance) -> bool {
+  tol.orientation(b1, b2, v) == 0 && tol.dot_exceeds(b1, b2, v)
+}
 
-      let th2 = angle(b2 - b1, a2 - b1);
-      if th2 >= 0_f64 && th2 < th - EPS {
-        return true;
-      }
-    }
+/// Whether one of the edges of the corner `a1`, at the same point as `b1`, goes inside
+/// the corner `b1`. An edge leaving along the edge leaving `b1` goes inside too, as
+/// both polygons lie on its left, unless the corner `b1` has no interior.
+fn edge_inside_corner(
+  (a0, _, a2): (Point, Point, Point),
+  b: (Point, Point, Point),
+  tol: &Tolerance,
+) -> bool {
+  let (b0, b1, b2) = b;
+  let spike = along_edge(b1, b2, b0, tol);
+
+  inside_corner(b, a0, tol) || inside_corner(b, a2, tol) || (!spike && along_edge(b1, b2, a2, tol))
+}
 
-    false
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_case::test_case;
+
+  /// Corner of a unit square far from the origin, where angles lose the precision
+  /// orientations keep, a point `dy` above its edge leaving the corner and a point
+  /// outside of it.
+  fn far_corner(dy: f64) -> ((Point, Point, Point), Point, Point) {
+    let point = |x: f64, y: f64| Point {
+      x: 1e8 + 0.1 + x,
+      y: 1e8 + 0.3 + y,
+    };
+    let corner = (
+      point(0_f64, 1_f64),
+      point(0_f64, 0_f64),
+      point(1_f64, 0_f64),
+    );
+
+    (corner, point(1_f64, dy), point(-1_f64, -1_f64))
+  }
+
+  /// The default tolerance, with rounded and with exact predicates.
+  fn both_modes() -> [Tolerance; 2] {
+    [false, true].map(|exact| Tolerance {
+      exact,
+      ..Tolerance::default()
+    })
+  }
+
+  #[test_case(-1e-3 ; "below")]
+  #[test_case(-1e-7 ; "at the band below")]
+  #[test_case(-1e-9 ; "within the band below")]
+  #[test_case(0_f64 ; "on the edge")]
+  #[test_case(1e-9 ; "within the band above")]
+  #[test_case(1e-7 ; "at the band above")]
+  #[test_case(1e-3 ; "above")]
+  fn test_edge_inside_corner_agrees_with_orientation(dy: f64) {
+    let (b, v, outside) = far_corner(dy);
+
+    for tol in both_modes() {
+      let orientation = tol.orientation(b.1, b.2, v);
+
+      assert_eq!(
+        edge_inside_corner((v, b.1, outside), b, &tol),
+        orientation > 0
+      );
+      assert_eq!(
+        edge_inside_corner((outside, b.1, v), b, &tol),
+        orientation >= 0
+      );
+    }
   }
 }
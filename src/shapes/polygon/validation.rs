@@ -0,0 +1,235 @@
+use std::fmt::Display;
+
+use crate::{
+  shapes::{point::Point, segment::Segment},
+  tolerance::Tolerance,
+};
+
+use super::Polygon;
+
+/// A problem that keeps a polygon from being a simple polygon. Vertices are numbered
+/// from 0 in the order of `Polygon::vertices`, and edge `i` goes from vertex `i` to the
+/// next one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Defect {
+  /// Fewer than 3 vertices, with the number of vertices.
+  TooFewVertices(usize),
+  /// A vertex with a coordinate that is infinite or NaN.
+  NotFinite(usize),
+  /// An edge whose ends are the same point.
+  ZeroLengthEdge(usize),
+  /// Two vertices that are not consecutive but are at the same point.
+  RepeatedVertex { vertices: (usize, usize), at: Point },
+  /// A vertex where the polygon turns back along the edge it came from, so that both
+  /// edges meeting there overlap.
+  Spike(usize),
+  /// Two edges that are not consecutive but cross or touch.
+  Crossing { edges: (usize, usize), at: Point },
+}
+
+impl Display for Defect {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::TooFewVertices(n) => write!(f, "{n} vertices, at least 3 are needed"),
+      Self::NotFinite(i) => write!(f, "vertex {i} is not finite"),
+      Self::ZeroLengthEdge(i) => write!(f, "edge {i} has zero length"),
+      Self::RepeatedVertex { vertices, at } => {
+        write!(
+          f,
+          "vertices {} and {} are both at {at}",
+          vertices.0, vertices.1
+        )
+      }
+      Self::Spike(i) => write!(f, "the edges meeting at vertex {i} overlap"),
+      Self::Crossing { edges, at } => write!(f, "edges {} and {} cross at {at}", edges.0, edges.1),
+    }
+  }
+}
+
+/// Whether the polygon turns back at `v`, along the edge from `prev`.
+fn spike((prev, v, next): (Point, Point, Point), tol: &Tolerance) -> bool {
+  tol.orientation(v, prev, next) == 0 && tol.dot_exceeds(v, prev, next)
+}
+
+/// Point where the lines through `a` and `b` cross.
+fn crossing(a: &Segment, b: &Segment) -> Point {
+  let (da, db) = (a.q - a.p, b.q - b.p);
+  let t = (b.p - a.p).cross(db) / da.cross(db);
+  Point {
+    x: da.x.mul_add(t, a.p.x),
+    y: da.y.mul_add(t, a.p.y),
+  }
+}
+
+/// Point where the edges `a` and `b` meet, other than at a shared vertex.
+fn contact(a: &Segment, b: &Segment, tol: &Tolerance) -> Option<Point> {
+  if a.intersects_with(b, tol) {
+    return Some(crossing(a, b));
+  }
+
+  [b.p, b.q]
+    .into_iter()
+    .find(|p| a.contains_except_endpoints(*p, tol))
+    .or_else(|| {
+      [a.p, a.q]
+        .into_iter()
+        .find(|p| b.contains_except_endpoints(*p, tol))
+    })
+}
+
+impl Polygon {
+  /// Every problem that keeps this polygon from being simple, in the order of its
+  /// vertices. Empty for a valid polygon.
+  #[must_use]
+  pub fn validate(&self, tol: &Tolerance) -> Vec<Defect> {
+    if self.len() < 3 {
+      return vec![Defect::TooFewVertices(self.len())];
+    }
+
+    let not_finite: Vec<Defect> = (0..self.len())
+      .filter(|i| !self.vertices[*i].x.is_finite() || !self.vertices[*i].y.is_finite())
+      .map(Defect::NotFinite)
+      .collect();
+    if !not_finite.is_empty() {
+      return not_finite;
+    }
+
+    (0..self.len())
+      .flat_map(|i| self.defects_at(i, tol))
+      .collect()
+  }
+
+  /// Defects of vertex `i` and edge `i`, and of the pairs they form with the vertices
+  /// and edges after them.
+  fn defects_at(&self, i: usize, tol: &Tolerance) -> Vec<Defect> {
+    let corner = self.vertices_at(i as i32);
+    let mut defects = vec![];
+
+    if tol.points_equal(corner.1, corner.2) {
+      defects.push(Defect::ZeroLengthEdge(i));
+    }
+    if spike(corner, tol) {
+      defects.push(Defect::Spike(i));
+    }
+
+    // Pairs that are not consecutive: the last vertex and edge come before the first.
+    let last = if i == 0 { self.len() - 1 } else { self.len() };
+    defects.extend((i + 2..last).flat_map(|j| self.pair_defects(i, j, tol)));
+    defects
+  }
+
+  /// Defects of vertices `i` and `j`, and of edges `i` and `j`, which are not
+  /// consecutive.
+  fn pair_defects(&self, i: usize, j: usize, tol: &Tolerance) -> Vec<Defect> {
+    let mut defects = vec![];
+
+    if tol.points_equal(self.vertices[i], self.vertices[j]) {
+      defects.push(Defect::RepeatedVertex {
+        vertices: (i, j),
+        at: self.vertices[i],
+      });
+    }
+    if let Some(at) = contact(&self.edge(i), &self.edge(j), tol) {
+      defects.push(Defect::Crossing { edges: (i, j), at });
+    }
+
+    defects
+  }
+
+  /// The same polygon without zero-length edges and spikes, removing one vertex at a
+  /// time until none is left. Other defects, such as crossing edges, cannot be
+  /// repaired and are kept.
+  #[must_use]
+  pub fn repaired(&self, tol: &Tolerance) -> Self {
+    let mut repaired = self.clone();
+
+    while repaired.len() > 2 {
+      let removable = (0..repaired.len()).position(|i| {
+        let corner = repaired.vertices_at(i as i32);
+        tol.points_equal(corner.0, corner.1) || spike(corner, tol)
+      });
+
+      match removable {
+        Some(i) => repaired.vertices.remove(i),
+        None => break,
+      };
+    }
+
+    repaired
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_case::test_case;
+
+  fn polygon(vertices: &[(f64, f64)]) -> Polygon {
+    Polygon::new(vertices.iter().map(|&(x, y)| Point { x, y }).collect())
+  }
+
+  #[test_case(&[(0., 0.), (2., 0.), (2., 2.), (0., 2.)], &[] ; "square")]
+  #[test_case(&[(0., 0.), (1., 0.)], &["2 vertices, at least 3 are needed"] ; "segment")]
+  #[test_case(&[(0., 0.), (f64::NAN, 0.), (0., 1.)], &["vertex 1 is not finite"] ; "nan")]
+  #[test_case(
+    &[(0., 0.), (2., 0.), (2., 0.), (0., 2.)],
+    &["edge 1 has zero length"] ;
+    "zero length edge"
+  )]
+  #[test_case(
+    &[(0., 0.), (2., 0.), (2., 2.), (4., 2.), (4., 4.), (2., 4.), (2., 2.), (0., 2.)],
+    &["vertices 2 and 6 are both at (2.000000, 2.000000)"] ;
+    "repeated vertex"
+  )]
+  #[test_case(
+    &[(0., 0.), (4., 0.), (4., 4.), (4., 6.), (4., 5.), (0., 4.)],
+    &[
+      "edges 2 and 4 cross at (4.000000, 5.000000)",
+      "the edges meeting at vertex 3 overlap",
+    ] ;
+    "spike"
+  )]
+  #[test_case(
+    &[(0., 0.), (2., 0.), (0., 2.), (2., 2.)],
+    &["edges 1 and 3 cross at (1.000000, 1.000000)"] ;
+    "bowtie"
+  )]
+  #[test_case(
+    &[(0., 0.), (4., 0.), (4., 2.), (2., 0.), (0., 2.)],
+    &[
+      "edges 0 and 2 cross at (2.000000, 0.000000)",
+      "edges 0 and 3 cross at (2.000000, 0.000000)",
+    ] ;
+    "touching"
+  )]
+  fn test_validate(vertices: &[(f64, f64)], defects: &[&str]) {
+    let defects: Vec<String> = defects.iter().map(|d| (*d).to_owned()).collect();
+    assert_eq!(
+      polygon(vertices)
+        .validate(&Tolerance::default())
+        .iter()
+        .map(Defect::to_string)
+        .collect::<Vec<String>>(),
+      defects
+    );
+  }
+
+  #[test_case(&[(0., 0.), (2., 0.), (2., 0.), (2., 2.), (0., 2.), (0., 0.)], 4 ; "repeated")]
+  #[test_case(&[(0., 0.), (2., 0.), (2., 2.), (2., 3.), (2., 2.), (0., 2.)], 4 ; "spike")]
+  #[test_case(&[(0., 0.), (2., 0.), (2., 2.), (0., 2.), (1., 2.)], 4 ; "spike at the end")]
+  fn test_repaired(vertices: &[(f64, f64)], len: usize) {
+    let tol = Tolerance::default();
+    let repaired = polygon(vertices).repaired(&tol);
+
+    assert_eq!(repaired.len(), len);
+    assert!(repaired.validate(&tol).is_empty());
+  }
+
+  #[test]
+  fn test_crossings_are_not_repaired() {
+    let tol = Tolerance::default();
+    let bowtie = polygon(&[(0., 0.), (2., 0.), (0., 2.), (2., 2.)]);
+
+    assert_eq!(bowtie.repaired(&tol).validate(&tol), bowtie.validate(&tol));
+  }
+}
@@ -1,101 +1,110 @@
-use std::collections::HashMap;
+use crate::{
+  shapes::{point::Point, point_snapper::PointSnapper, segment::Segment},
+  tolerance::Tolerance,
+};
 
-use crate::shapes::{point::Point, segment::Segment};
-
-struct Node {
-  value: Point,
-  neighbors: [Option<usize>; 2],
+/// Graph of the points joined by segments, split into polylines on demand. Segment
+/// endpoints closer than the tolerance are treated as the same point.
+pub struct PolylineSet {
+  snapper: PointSnapper,
+  links: Vec<(usize, usize)>,
 }
 
-impl Node {
-  const fn new(p: Point) -> Self {
-    Self {
-      value: p,
-      neighbors: [None, None],
-    }
+impl PolylineSet {
+  fn put(&mut self, p: Point, q: Point) {
+    let idx_p = self.snapper.insert(p);
+    let idx_q = self.snapper.insert(q);
+    self.links.push((idx_p, idx_q));
   }
 
-  fn is_full(&self) -> bool {
-    self.neighbors.iter().all(Option::is_some)
-  }
+  /// Snapped points, and the node each inserted point belongs to.
+  fn nodes(&self) -> (Vec<Point>, Vec<usize>) {
+    let mut points = vec![];
+    let mut node_idx = vec![0; self.snapper.len()];
 
-  fn add_link(&mut self, node_idx: usize) {
-    assert!(!self.is_full(), "cannot add neighbors");
+    for idx in 0..self.snapper.len() {
+      let root = self.snapper.find(idx);
 
-    if self.neighbors[0].is_none() {
-      self.neighbors[0] = Some(node_idx);
-    } else {
-      self.neighbors[1] = Some(node_idx);
+      if root == idx {
+        points.push(self.snapper.point(idx));
+        node_idx[idx] = points.len() - 1;
+      } else {
+        node_idx[idx] = node_idx[root];
+      }
     }
-  }
-}
 
-pub struct PolylineSet {
-  nodes: Vec<Node>,
-  point_to_node_idx: HashMap<Point, usize>,
-}
-
-impl PolylineSet {
-  fn find_or_create_index(&mut self, p: Point) -> usize {
-    if let Some(idx) = self.point_to_node_idx.get(&p) {
-      *idx
-    } else {
-      self.nodes.push(Node::new(p));
-      let new_idx = self.nodes.len() - 1;
-      self.point_to_node_idx.insert(p, new_idx);
-      new_idx
-    }
+    (points, node_idx)
   }
 
-  fn put(&mut self, p: Point, q: Point) {
-    let idx_p = self.find_or_create_index(p);
-    let idx_q = self.find_or_create_index(q);
-    self.nodes[idx_p].add_link(idx_q);
-    self.nodes[idx_q].add_link(idx_p);
-  }
+  /// Neighbors of every node, paired with the index of the link that reaches them.
+  /// Repeated links and links between snapped points are ignored.
+  fn adjacency(&self, node_idx: &[usize]) -> Vec<Vec<(usize, usize)>> {
+    let mut adjacency: Vec<Vec<(usize, usize)>> = vec![vec![]; node_idx.len()];
 
-  fn dfs(&self, u: usize, visited: &mut Vec<bool>, polyline: &mut Vec<Point>) {
-    if visited[u] {
-      return;
+    for (link_idx, (p, q)) in self.links.iter().enumerate() {
+      let (u, v) = (node_idx[*p], node_idx[*q]);
+
+      if u != v && adjacency[u].iter().all(|(w, _)| *w != v) {
+        adjacency[u].push((v, link_idx));
+        adjacency[v].push((u, link_idx));
+      }
     }
 
-    visited[u] = true;
+    adjacency
+  }
 
-    polyline.push(self.nodes[u].value);
+  /// Follows unvisited links from `u` through every node of degree 2, stopping at an
+  /// endpoint, a branching node or when the walk comes back to a visited link.
+  fn walk(
+    points: &[Point],
+    adjacency: &[Vec<(usize, usize)>],
+    mut u: usize,
+    visited: &mut [bool],
+  ) -> Vec<Point> {
+    let mut polyline = vec![points[u]];
+
+    while let Some(&(v, link_idx)) = adjacency[u].iter().find(|(_, l)| !visited[*l]) {
+      visited[link_idx] = true;
+      polyline.push(points[v]);
+
+      if adjacency[v].len() != 2 {
+        break;
+      }
 
-    for v in self.nodes[u].neighbors.iter().flatten() {
-      self.dfs(*v, visited, polyline);
+      u = v;
     }
+
+    polyline
   }
 
+  /// Maximal chains of segments. Chains are split at points where three or more
+  /// segments meet. A closed loop ends with the same point it starts with.
   #[must_use]
   pub fn get_polylines(&self) -> Vec<Vec<Point>> {
-    let mut visited = vec![false; self.nodes.len()];
-
+    let (points, node_idx) = self.nodes();
+    let adjacency = self.adjacency(&node_idx);
+    let mut visited = vec![false; self.links.len()];
     let mut polylines: Vec<Vec<Point>> = vec![];
 
-    for node_idx in 0..self.nodes.len() {
-      if self.nodes[node_idx].is_full() {
-        continue;
-      }
+    let chain_ends = (0..points.len()).filter(|u| adjacency[*u].len() != 2);
+    let loop_starts = (0..points.len()).filter(|u| adjacency[*u].len() == 2);
 
-      if visited[node_idx] {
-        continue;
+    for u in chain_ends.chain(loop_starts) {
+      while adjacency[u].iter().any(|(_, l)| !visited[*l]) {
+        polylines.push(Self::walk(&points, &adjacency, u, &mut visited));
       }
-
-      let mut polyline = Vec::<Point>::new();
-      self.dfs(node_idx, &mut visited, &mut polyline);
-      polylines.push(polyline);
     }
 
     polylines
   }
 
+  /// Polylines made of the `segments`, with endpoints closer than the linear tolerance
+  /// snapped together.
   #[must_use]
-  pub fn from_segments(segments: &[Segment]) -> Self {
+  pub fn from_segments(segments: &[Segment], tol: &Tolerance) -> Self {
     let mut polylines = Self {
-      nodes: vec![],
-      point_to_node_idx: HashMap::new(),
+      snapper: PointSnapper::new(tol.linear),
+      links: vec![],
     };
 
     for s in segments {
@@ -138,7 +147,7 @@ mod tests {
 
   #[test]
   fn test_polyline_set_put() {
-    let mut set = PolylineSet::from_segments(&[]);
+    let mut set = PolylineSet::from_segments(&[], &Tolerance::default());
     assert_eq!(set.get_polylines().len(), 0);
 
     let Segment { p, q } = seg(0, 0, 0, 1);
@@ -177,21 +186,121 @@ mod tests {
         .collect();
       segments.shuffle(&mut thread_rng());
 
-      let set = PolylineSet::from_segments(&segments);
+      let set = PolylineSet::from_segments(&segments, &Tolerance::default());
       assert_eq!(set.get_polylines().len(), set_size);
     }
   }
 
+  fn sorted_lengths(set: &PolylineSet) -> Vec<usize> {
+    let mut lengths: Vec<usize> = set.get_polylines().iter().map(Vec::len).collect();
+    lengths.sort_unstable();
+    lengths
+  }
+
   #[test]
-  #[should_panic]
-  fn test_polyline_set_from_segments_panic() {
+  fn test_polyline_set_branching() {
     let a = seg(0, 0, 1, 0);
     let b = seg(2, 0, 3, 0);
     let c = seg(1, 0, 2, 0);
 
-    let mut polyline_set = PolylineSet::from_segments(&[a, b, c]);
+    let mut polyline_set = PolylineSet::from_segments(&[a, b, c], &Tolerance::default());
+    assert_eq!(sorted_lengths(&polyline_set), vec![4]);
 
     let Segment { p, q } = seg(1, 0, 1, 2);
     polyline_set.put(p, q);
+    assert_eq!(sorted_lengths(&polyline_set), vec![2, 2, 3]);
+  }
+
+  #[test]
+  fn test_polyline_set_closed_loop() {
+    for _ in 0..1000 {
+      let mut segments = vec![
+        seg(0, 0, 1, 0),
+        seg(1, 0, 1, 1),
+        seg(1, 1, 0, 1),
+        seg(0, 1, 0, 0),
+      ];
+      segments.shuffle(&mut thread_rng());
+
+      let polylines = PolylineSet::from_segments(&segments, &Tolerance::default()).get_polylines();
+      assert_eq!(polylines.len(), 1);
+      assert_eq!(polylines[0].len(), 5);
+      assert_eq!(polylines[0].first(), polylines[0].last());
+    }
+  }
+
+  /// Two triangles meeting at the origin, and an edge leaving it.
+  fn loops_sharing_a_point() -> Vec<Segment> {
+    vec![
+      seg(0, 0, 1, 1),
+      seg(1, 1, 1, -1),
+      seg(1, -1, 0, 0),
+      seg(0, 0, -1, 1),
+      seg(-1, 1, -1, -1),
+      seg(-1, -1, 0, 0),
+      seg(0, 0, 0, 5),
+    ]
+  }
+
+  #[test]
+  fn test_polyline_set_loops_sharing_a_point() {
+    for _ in 0..1000 {
+      let mut segments = loops_sharing_a_point();
+      segments.shuffle(&mut thread_rng());
+
+      let set = PolylineSet::from_segments(&segments, &Tolerance::default());
+      assert_eq!(sorted_lengths(&set), vec![2, 4, 4]);
+
+      for polyline in set.get_polylines().iter().filter(|p| p.len() == 4) {
+        assert_eq!(polyline.first(), polyline.last());
+      }
+    }
+  }
+
+  #[test]
+  fn test_polyline_set_repeated_segments() {
+    let mut set =
+      PolylineSet::from_segments(&[seg(0, 0, 1, 0), seg(1, 0, 0, 0)], &Tolerance::default());
+    let Segment { p, .. } = seg(0, 0, 1, 0);
+    set.put(p, p);
+    assert_eq!(sorted_lengths(&set), vec![2]);
+  }
+
+  #[test]
+  fn test_polyline_set_snaps_across_rounding_boundaries() {
+    let a = Point { x: 0_f64, y: 0_f64 };
+    let b = Point {
+      x: 0.000_499_999_99,
+      y: 0_f64,
+    };
+    let c = Point {
+      x: 0.000_500_000_01,
+      y: 0_f64,
+    };
+    let d = Point { x: 1_f64, y: 0_f64 };
+
+    let set = PolylineSet::from_segments(&[a.seg(b), c.seg(d)], &Tolerance::default());
+    assert_eq!(sorted_lengths(&set), vec![3]);
+  }
+
+  #[test_case(1_f64, &[2, 2])]
+  #[test_case(1e5, &[3])]
+  fn test_polyline_set_tolerance(scale: f64, lengths: &[usize]) {
+    let segments = [
+      seg(1000, 1000, 2000, 1000),
+      Segment {
+        p: Point {
+          x: 2000.005,
+          y: 999.995,
+        },
+        q: Point {
+          x: 3000_f64,
+          y: 1000_f64,
+        },
+      },
+    ];
+
+    let set = PolylineSet::from_segments(&segments, &Tolerance::from_scale(scale));
+    assert_eq!(sorted_lengths(&set), lengths.to_vec());
   }
 }
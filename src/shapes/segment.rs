@@ -1,7 +1,6 @@
 use crate::{
-  constants::EPS,
+  tolerance::Tolerance,
   traits::{common_boundary::CommonBoundary, intersection::Intersects},
-  util::{equal, orientation},
 };
 
 use super::point::Point;
@@ -14,40 +13,51 @@ pub struct Segment {
 
 impl Intersects for Segment {
   fn intersects(&self, other: &Self) -> bool {
-    let o1 = orientation(self.p, self.q, other.p);
-    let o2 = orientation(self.p, self.q, other.q);
+    self.intersects_with(other, &Tolerance::default())
+  }
+}
+
+impl CommonBoundary<Option<Self>> for Segment {
+  fn common_boundary(&self, other: &Self) -> Option<Self> {
+    self.common_boundary_with(other, &Tolerance::default())
+  }
+}
+
+impl Segment {
+  /// Whether both segments cross at a single point inside both of them.
+  #[must_use]
+  pub fn intersects_with(&self, other: &Self, tol: &Tolerance) -> bool {
+    let o1 = tol.orientation(self.p, self.q, other.p);
+    let o2 = tol.orientation(self.p, self.q, other.q);
 
     if o1 * o2 >= 0 {
       return false;
     }
 
-    let o3 = orientation(other.p, other.q, self.p);
-    let o4 = orientation(other.p, other.q, self.q);
+    let o3 = tol.orientation(other.p, other.q, self.p);
+    let o4 = tol.orientation(other.p, other.q, self.q);
     o3 * o4 < 0
   }
-}
 
-impl CommonBoundary<Option<Self>> for Segment {
-  fn common_boundary(&self, other: &Self) -> Option<Self> {
-    if self.contains(other.p) && self.contains(other.q) {
+  #[must_use]
+  pub fn common_boundary_with(&self, other: &Self, tol: &Tolerance) -> Option<Self> {
+    if self.contains(other.p, tol) && self.contains(other.q, tol) {
       Some(*other)
-    } else if other.contains(self.p) && other.contains(self.q) {
+    } else if other.contains(self.p, tol) && other.contains(self.q, tol) {
       Some(*self)
     } else {
-      self.common_boundary_aux(other)
+      self.common_boundary_aux(other, tol)
     }
   }
-}
 
-impl Segment {
-  fn common_boundary_aux(&self, s: &Self) -> Option<Self> {
-    if self.contains(s.p) && s.contains(self.p) {
+  fn common_boundary_aux(&self, s: &Self, tol: &Tolerance) -> Option<Self> {
+    if self.contains(s.p, tol) && s.contains(self.p, tol) {
       Some(self.p.seg(s.p))
-    } else if self.contains(s.p) && s.contains(self.q) {
+    } else if self.contains(s.p, tol) && s.contains(self.q, tol) {
       Some(self.q.seg(s.p))
-    } else if self.contains(s.q) && s.contains(self.p) {
+    } else if self.contains(s.q, tol) && s.contains(self.p, tol) {
       Some(self.p.seg(s.q))
-    } else if self.contains(s.q) && s.contains(self.q) {
+    } else if self.contains(s.q, tol) && s.contains(self.q, tol) {
       Some(self.q.seg(s.q))
     } else {
       None
@@ -55,23 +65,23 @@ impl Segment {
   }
 
   #[must_use]
-  pub fn is_horizontal(&self) -> bool {
-    equal(self.p.y, self.q.y)
+  pub fn is_horizontal(&self, tol: &Tolerance) -> bool {
+    tol.equal(self.p.y, self.q.y)
   }
 
   #[must_use]
-  pub fn face_right(&self) -> bool {
-    !self.is_horizontal() && self.p.y < self.q.y
+  pub fn face_right(&self, tol: &Tolerance) -> bool {
+    !self.is_horizontal(tol) && self.p.y < self.q.y
   }
 
   #[must_use]
-  pub fn face_left(&self) -> bool {
-    !self.is_horizontal() && self.p.y > self.q.y
+  pub fn face_left(&self, tol: &Tolerance) -> bool {
+    !self.is_horizontal(tol) && self.p.y > self.q.y
   }
 
   #[must_use]
-  pub fn horizontal_distance(&self, v: Point) -> f64 {
-    if equal(self.p.x, self.q.x) {
+  pub fn horizontal_distance(&self, v: Point, tol: &Tolerance) -> f64 {
+    if tol.equal(self.p.x, self.q.x) {
       v.x - self.p.x
     } else {
       let slope = (self.q.y - self.p.y) / (self.q.x - self.p.x);
@@ -81,20 +91,36 @@ impl Segment {
   }
 
   #[must_use]
-  pub fn contains_except_endpoints(&self, r: Point) -> bool {
-    if orientation(self.p, self.q, r) == 0 {
-      (self.q - self.p) * (r - self.p) > EPS && (self.p - self.q) * (r - self.q) > EPS
+  pub fn contains_except_endpoints(&self, r: Point, tol: &Tolerance) -> bool {
+    if tol.orientation(self.p, self.q, r) == 0 {
+      tol.dot_exceeds(self.p, self.q, r) && tol.dot_exceeds(self.q, self.p, r)
     } else {
       false
     }
   }
 
+  /// The segment moved `offset` units along the X axis.
+  #[must_use]
+  pub fn shifted(&self, offset: f64) -> Self {
+    let shift = |p: Point| Point {
+      x: p.x + offset,
+      y: p.y,
+    };
+
+    Self {
+      p: shift(self.p),
+      q: shift(self.q),
+    }
+  }
+
   #[must_use]
   pub fn length(&self) -> f64 {
     self.p.dist(self.q)
   }
 
-  fn contains(&self, r: Point) -> bool {
-    self.p == r || self.q == r || self.contains_except_endpoints(r)
+  fn contains(&self, r: Point, tol: &Tolerance) -> bool {
+    tol.points_equal(self.p, r)
+      || tol.points_equal(self.q, r)
+      || self.contains_except_endpoints(r, tol)
   }
 }
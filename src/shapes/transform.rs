@@ -0,0 +1,187 @@
+use std::f64::consts::PI;
+
+use super::{point::Point, polygon::Polygon};
+
+/// Placement of a polygon relative to its input coordinates.
+///
+/// A point is first mirrored across the Y axis (when `reflected` is set), then rotated
+/// counterclockwise by `rotation` radians around the origin, and finally moved by
+/// `translation`. Negating a polygon is a rotation by π, so it is folded into `rotation`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+  pub rotation: f64,
+  pub reflected: bool,
+  pub translation: Point,
+}
+
+impl Default for Transform {
+  fn default() -> Self {
+    Self {
+      rotation: 0_f64,
+      reflected: false,
+      translation: Point { x: 0_f64, y: 0_f64 },
+    }
+  }
+}
+
+impl Transform {
+  #[must_use]
+  pub fn rotation(rotation: f64) -> Self {
+    Self {
+      rotation,
+      ..Self::default()
+    }
+  }
+
+  #[must_use]
+  pub fn translation(translation: Point) -> Self {
+    Self {
+      translation,
+      ..Self::default()
+    }
+  }
+
+  #[must_use]
+  pub fn half_turn() -> Self {
+    Self::rotation(PI)
+  }
+
+  #[must_use]
+  pub fn apply(&self, p: Point) -> Point {
+    let p = if self.reflected {
+      Point { x: -p.x, y: p.y }
+    } else {
+      p
+    };
+
+    p.rot_ccw(self.rotation) + self.translation
+  }
+
+  /// Applies the transform to every vertex. Mirroring reverses the vertex order so
+  /// the orientation of the polygon is preserved.
+  #[must_use]
+  pub fn apply_polygon(&self, polygon: &Polygon) -> Polygon {
+    let mut vertices: Vec<Point> = polygon.vertices.iter().map(|p| self.apply(*p)).collect();
+
+    if self.reflected {
+      vertices.reverse();
+    }
+
+    Polygon::new(vertices)
+  }
+
+  #[must_use]
+  pub fn inverse(&self) -> Self {
+    let inverse = Self {
+      rotation: if self.reflected {
+        self.rotation
+      } else {
+        -self.rotation
+      },
+      reflected: self.reflected,
+      translation: Point { x: 0_f64, y: 0_f64 },
+    };
+
+    Self {
+      translation: inverse.apply(self.translation).negate(),
+      ..inverse
+    }
+  }
+
+  /// Transform equivalent to applying `self` first and `other` afterwards.
+  #[must_use]
+  pub fn then(&self, other: &Self) -> Self {
+    let rotation = if other.reflected {
+      other.rotation - self.rotation
+    } else {
+      other.rotation + self.rotation
+    };
+
+    Self {
+      rotation: rotation.rem_euclid(2_f64 * PI),
+      reflected: self.reflected ^ other.reflected,
+      translation: other.apply(self.translation),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::tolerance::Tolerance;
+  use test_case::test_case;
+
+  const fn point(x: f64, y: f64) -> Point {
+    Point { x, y }
+  }
+
+  fn assert_close(a: Point, b: Point) {
+    assert!(Tolerance::default().points_equal(a, b), "{a:?} != {b:?}");
+  }
+
+  #[test_case(Transform::default(), point(3_f64, 4_f64), point(3_f64, 4_f64))]
+  #[test_case(Transform::half_turn(), point(3_f64, 4_f64), point(-3_f64, -4_f64))]
+  #[test_case(Transform::rotation(PI / 2_f64), point(1_f64, 0_f64), point(0_f64, 1_f64))]
+  #[test_case(
+    Transform::translation(point(1_f64, 2_f64)),
+    point(3_f64, 4_f64),
+    point(4_f64, 6_f64)
+  )]
+  #[test_case(Transform { reflected: true, ..Transform::default() }, point(3_f64, 4_f64), point(-3_f64, 4_f64))]
+  fn test_apply(t: Transform, p: Point, res: Point) {
+    assert_close(t.apply(p), res);
+  }
+
+  fn transforms() -> [Transform; 4] {
+    [
+      Transform::rotation(0.3),
+      Transform::translation(point(-2_f64, 5_f64)),
+      Transform {
+        rotation: 1.2,
+        reflected: true,
+        translation: point(4_f64, 1_f64),
+      },
+      Transform::half_turn(),
+    ]
+  }
+
+  #[test]
+  fn test_then() {
+    let p = point(1.5, -7_f64);
+
+    for a in &transforms() {
+      for b in &transforms() {
+        assert_close(a.then(b).apply(p), b.apply(a.apply(p)));
+      }
+    }
+  }
+
+  #[test_case(Transform::rotation(0.3))]
+  #[test_case(Transform::translation(point(-2_f64, 5_f64)))]
+  #[test_case(Transform { rotation: 1.2, reflected: true, translation: point(4_f64, 1_f64) })]
+  #[test_case(Transform { rotation: 5.1, reflected: false, translation: point(3_f64, -8_f64) })]
+  fn test_inverse(t: Transform) {
+    let p = point(1.5, -7_f64);
+    assert_close(t.inverse().apply(t.apply(p)), p);
+    assert_close(t.apply(t.inverse().apply(p)), p);
+  }
+
+  #[test]
+  fn test_apply_polygon_keeps_orientation() {
+    let polygon = Polygon::new(vec![
+      point(0_f64, 0_f64),
+      point(2_f64, 0_f64),
+      point(0_f64, 1_f64),
+    ]);
+
+    let t = Transform {
+      reflected: true,
+      ..Transform::default()
+    };
+
+    let mirrored = t.apply_polygon(&polygon);
+    assert_eq!(mirrored.vertices[0], point(0_f64, 1_f64));
+    assert_eq!(mirrored.vertices[1], point(-2_f64, 0_f64));
+    assert_eq!(mirrored.vertices[2], point(0_f64, 0_f64));
+  }
+}
@@ -0,0 +1,88 @@
+use polygonal_puzzle::polygon_matcher::stats::SearchStats;
+
+const HEADER: [&str; 6] = [
+  "pairs",
+  "shifts",
+  "skipped",
+  "tests",
+  "overlaps",
+  "boundaries",
+];
+
+const fn cells(stats: &SearchStats) -> [u64; 6] {
+  [
+    stats.pairs,
+    stats.shifts,
+    stats.skipped_shifts,
+    stats.overlap_tests,
+    stats.overlaps,
+    stats.boundaries,
+  ]
+}
+
+fn line<T: ToString>(label: &str, cells: &[T]) -> String {
+  cells.iter().fold(format!("{label:<6}"), |line, cell| {
+    format!("{line}{:>12}", cell.to_string())
+  })
+}
+
+/// Work done on each case, one line per case in the order added, then the total.
+#[derive(Default)]
+pub struct StatsTable {
+  cases: usize,
+  total: SearchStats,
+}
+
+impl StatsTable {
+  pub fn header() -> String {
+    line("case", &HEADER)
+  }
+
+  /// Line of the next case, which is also added to the total.
+  pub fn add(&mut self, stats: &SearchStats) -> String {
+    self.cases += 1;
+    self.total += *stats;
+    line(&self.cases.to_string(), &cells(stats))
+  }
+
+  pub fn total(&self) -> String {
+    line("total", &cells(&self.total))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_stats_table() {
+    let stats = SearchStats {
+      pairs: 2,
+      overlaps: 20,
+      ..SearchStats::default()
+    };
+    let mut table = StatsTable::default();
+    table.add(&stats);
+
+    assert_eq!(
+      table.add(&stats),
+      "2                2           0           0           0          20           0"
+    );
+    assert_eq!(
+      table.total(),
+      "total            4           0           0           0          40           0"
+    );
+  }
+
+  #[test]
+  fn test_header_is_aligned() {
+    assert_eq!(
+      StatsTable::header(),
+      "case         pairs      shifts     skipped       tests    overlaps  boundaries"
+    );
+    assert_eq!(
+      StatsTable::header().len(),
+      StatsTable::default().total().len()
+    );
+  }
+}
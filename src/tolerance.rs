@@ -0,0 +1,177 @@
+use crate::{constants::EPS, predicates, shapes::point::Point, shapes::polygon::Polygon};
+
+/// Inputs spanning this many units get the default tolerances.
+const REFERENCE_EXTENT: f64 = 100_f64;
+
+/// Tolerances used to compare lengths and areas. They grow with the size of
+/// the input, so scaling a puzzle by any factor gives the same answer, scaled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+  /// Coordinates and lengths closer than this are equal.
+  pub linear: f64,
+  /// Cross and dot products smaller than this are zero.
+  pub area: f64,
+  /// Decide orientations and dot products exactly instead of with rounded `f64`
+  /// products, so that predicates on the same points never contradict each other.
+  pub exact: bool,
+}
+
+/// Largest side of the bounding box of the polygons.
+fn extent(polygons: &[&Polygon]) -> f64 {
+  let span = |coordinate: fn(&Point) -> f64| {
+    let (min, max) = polygons
+      .iter()
+      .flat_map(|p| p.vertices.iter())
+      .map(coordinate)
+      .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+        (min.min(v), max.max(v))
+      });
+    max - min
+  };
+
+  span(|p| p.x).max(span(|p| p.y))
+}
+
+impl Default for Tolerance {
+  /// Absolute tolerances, suited for inputs spanning about a hundred units.
+  fn default() -> Self {
+    Self::from_scale(1_f64)
+  }
+}
+
+impl Tolerance {
+  /// Tolerances for inputs measured in units `scale` times larger than the default.
+  #[must_use]
+  pub fn from_scale(scale: f64) -> Self {
+    Self {
+      linear: EPS * scale,
+      area: EPS * scale * scale,
+      exact: false,
+    }
+  }
+
+  /// Same tolerances for the inputs scaled by `factor`.
+  #[must_use]
+  pub fn scaled(&self, factor: f64) -> Self {
+    Self {
+      linear: self.linear * factor.abs(),
+      area: self.area * factor * factor,
+      exact: self.exact,
+    }
+  }
+
+  /// Tolerances derived from the bounding box of all the polygons. Falls back to the
+  /// default when the polygons have no extent.
+  #[must_use]
+  pub fn from_polygons(polygons: &[&Polygon]) -> Self {
+    let extent = extent(polygons);
+
+    if extent.is_normal() && extent > 0_f64 {
+      Self::from_scale(extent / REFERENCE_EXTENT)
+    } else {
+      Self::default()
+    }
+  }
+
+  #[inline]
+  #[must_use]
+  pub fn zero(&self, x: f64) -> bool {
+    x.abs() < self.linear
+  }
+
+  #[inline]
+  #[must_use]
+  pub fn equal(&self, a: f64, b: f64) -> bool {
+    self.zero(a - b)
+  }
+
+  #[inline]
+  #[must_use]
+  pub fn points_equal(&self, a: Point, b: Point) -> bool {
+    self.equal(a.x, b.x) && self.equal(a.y, b.y)
+  }
+
+  /// Sign of the turn `o -> a -> b`, with 0 when the three points are collinear.
+  #[must_use]
+  pub fn orientation(&self, o: Point, a: Point, b: Point) -> i8 {
+    if self.exact {
+      return predicates::orientation(o, a, b, self.area);
+    }
+
+    let cross = (a - o).cross(b - o);
+
+    if cross.abs() < self.area {
+      0
+    } else if cross > 0_f64 {
+      1
+    } else {
+      -1
+    }
+  }
+
+  /// Whether `(a - o) · (b - o)` is positive beyond the tolerance.
+  #[must_use]
+  pub fn dot_exceeds(&self, o: Point, a: Point, b: Point) -> bool {
+    if self.exact {
+      predicates::dot_exceeds(o, a, b, self.area)
+    } else {
+      (a - o) * (b - o) > self.area
+    }
+  }
+
+  #[inline]
+  #[must_use]
+  pub fn ccw(&self, o: Point, a: Point, b: Point) -> bool {
+    self.orientation(o, a, b) == 1
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_case::test_case;
+
+  fn square(side: f64) -> Polygon {
+    Polygon::new(vec![
+      Point { x: 0_f64, y: 0_f64 },
+      Point { x: side, y: 0_f64 },
+      Point { x: side, y: side },
+      Point { x: 0_f64, y: side },
+    ])
+  }
+
+  #[test_case(100_f64, Tolerance::default())]
+  #[test_case(1_f64, Tolerance::from_scale(0.01))]
+  #[test_case(0_f64, Tolerance::default())]
+  fn test_from_polygons(side: f64, tolerance: Tolerance) {
+    assert_eq!(Tolerance::from_polygons(&[&square(side)]), tolerance);
+  }
+
+  #[test]
+  fn test_scaled() {
+    assert_eq!(
+      Tolerance::default().scaled(-20_f64),
+      Tolerance::from_scale(20_f64)
+    );
+  }
+
+  #[test]
+  fn test_orientation_scales() {
+    let (o, a, b) = (
+      Point { x: 0_f64, y: 0_f64 },
+      Point { x: 1_f64, y: 0_f64 },
+      Point { x: 2_f64, y: 1e-6 },
+    );
+
+    for scale in [1e-3, 1_f64, 1e3] {
+      let tolerance = Tolerance::from_scale(scale);
+      let scaled = |p: Point| Point {
+        x: p.x * scale,
+        y: p.y * scale,
+      };
+
+      assert_eq!(tolerance.orientation(scaled(o), scaled(a), scaled(b)), 1);
+      assert_eq!(tolerance.orientation(scaled(o), scaled(a), scaled(a)), 0);
+    }
+  }
+}
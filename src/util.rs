@@ -1,16 +1,11 @@
 use std::cmp::Ordering;
 
-use crate::{constants::EPS, shapes::point::Point};
-
-#[inline]
-fn zero(x: f64) -> bool {
-  x.abs() < EPS
-}
+use crate::{shapes::point::Point, tolerance::Tolerance};
 
 #[inline]
 #[must_use]
 pub fn equal(a: f64, b: f64) -> bool {
-  zero(a - b)
+  Tolerance::default().equal(a, b)
 }
 
 #[must_use]
@@ -25,21 +20,13 @@ pub fn angle(a: Point, b: Point) -> f64 {
 
 #[must_use]
 pub fn orientation(o: Point, a: Point, b: Point) -> i8 {
-  let cross = (a - o).cross(b - o);
-
-  if zero(cross) {
-    0
-  } else if cross > 0_f64 {
-    1
-  } else {
-    -1
-  }
+  Tolerance::default().orientation(o, a, b)
 }
 
 #[inline]
 #[must_use]
 pub fn ccw(o: Point, a: Point, b: Point) -> bool {
-  orientation(o, a, b) == 1
+  Tolerance::default().ccw(o, a, b)
 }
 
 #[inline]
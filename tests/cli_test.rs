@@ -0,0 +1,130 @@
+use std::{
+  fs,
+  io::Write,
+  path::{Path, PathBuf},
+  process::{Command, Output, Stdio},
+};
+
+static INPUT_DATA: &str = include_str!("./data/input");
+
+/// The first `count` cases of the official input.
+fn first_cases(count: usize) -> String {
+  let mut lines = INPUT_DATA.lines();
+  let mut input = vec![];
+
+  for _ in 0..2 * count {
+    let n = lines.next().expect("should have a vertex count");
+    input.push(n);
+    input.extend(lines.by_ref().take(n.parse().expect("should be a count")));
+  }
+
+  input.join("\n") + "\n"
+}
+
+/// Runs the binary with `args` in a new directory, and returns its output and the
+/// directory. The run may fail.
+fn run(name: &str, input: &str, args: &[&str]) -> (Output, PathBuf) {
+  let dir = std::env::temp_dir().join(format!("polygonal_puzzle_{}_{name}", std::process::id()));
+  fs::create_dir_all(dir.join("results")).unwrap();
+
+  let mut child = Command::new(env!("CARGO_BIN_EXE_polygonal_puzzle"))
+    .args(args)
+    .current_dir(&dir)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .unwrap();
+  child
+    .stdin
+    .take()
+    .unwrap()
+    .write_all(input.as_bytes())
+    .unwrap();
+
+  (child.wait_with_output().unwrap(), dir)
+}
+
+/// Name and contents of every file the run wrote, sorted by name.
+fn results(dir: &Path) -> Vec<(String, Vec<u8>)> {
+  let mut files: Vec<(String, Vec<u8>)> = fs::read_dir(dir.join("results"))
+    .unwrap()
+    .map(|entry| {
+      let path = entry.unwrap().path();
+      let name = path.file_name().unwrap().to_string_lossy().into_owned();
+      (name, fs::read(&path).unwrap())
+    })
+    .collect();
+  files.sort();
+  files
+}
+
+#[test]
+fn test_repeated_runs_are_identical() {
+  let input = first_cases(8);
+  let (output1, dir1) = run("single", &input, &["--top", "2", "--threads", "1"]);
+  let (output2, dir2) = run("parallel", &input, &["--top", "2", "--threads", "4"]);
+  let (results1, results2) = (results(&dir1), results(&dir2));
+  assert!(output1.status.success() && output2.status.success());
+
+  fs::remove_dir_all(dir1).unwrap();
+  fs::remove_dir_all(dir2).unwrap();
+
+  assert_eq!(output1.stdout, output2.stdout);
+  assert!(results1.len() > 8);
+  assert_eq!(results1, results2);
+}
+
+#[test]
+fn test_stats() {
+  let (output, dir) = run("stats", &first_cases(3), &["--stats"]);
+  fs::remove_dir_all(dir).unwrap();
+  assert!(output.status.success());
+
+  let stderr = String::from_utf8(output.stderr).unwrap();
+  let lines: Vec<&str> = stderr.lines().collect();
+
+  assert_eq!(lines.len(), 5);
+  assert!(lines[0].starts_with("case"));
+  assert!(lines[1].starts_with('1'));
+  assert!(lines[4].starts_with("total"));
+  assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 3);
+}
+
+#[test]
+fn test_malformed_input() {
+  let input = first_cases(2) + "3\n0 0\n1 x\n";
+  let (output, dir) = run("malformed", &input, &["--threads", "2"]);
+  fs::remove_dir_all(dir).unwrap();
+
+  assert_eq!(output.status.code(), Some(1));
+  assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 2);
+
+  let line = input.lines().count();
+  assert_eq!(
+    String::from_utf8(output.stderr).unwrap(),
+    format!("line {line}, column 3: invalid coordinate \"x\": invalid float literal\n")
+  );
+}
+
+#[test]
+fn test_validate() {
+  let input = first_cases(1) + "4\n0 0\n2 0\n0 2\n2 2\n3\n0 0\n1 0\n0 1\n" + &first_cases(1);
+  let (output, dir) = run("validate", &input, &["--validate", "repair"]);
+  fs::remove_dir_all(dir).unwrap();
+
+  assert_eq!(output.status.code(), Some(0));
+  let stdout = String::from_utf8(output.stdout).unwrap();
+  let lines: Vec<&str> = stdout.lines().collect();
+  assert_eq!(lines.len(), 3);
+  assert_eq!(lines[1], "");
+  assert_eq!(lines[0], lines[2]);
+
+  let line = first_cases(1).lines().count() + 1;
+  assert_eq!(
+    String::from_utf8(output.stderr).unwrap(),
+    format!(
+      "case 2 rejected: degenerate polygon: polygon on line {line}: edges 1 and 3 cross at (1.000000, 1.000000)\n"
+    )
+  );
+}
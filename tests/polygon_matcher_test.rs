@@ -4,15 +4,31 @@ mod test_case;
 mod macros;
 use crate::test_case::TestCase;
 use polygonal_puzzle::{
-  polygon_matcher,
-  shapes::{polygon::Polygon, polyline_set::PolylineSet, segment::Segment},
-  traits::common_boundary::CommonBoundary,
+  error::PuzzleError,
+  polygon_matcher::{
+    self,
+    budget::Budget,
+    exact::best_match_exact,
+    monitor::CancelToken,
+    options::{MatchOptions, Parallelism, RotationSet},
+    prepared::PreparedPolygon,
+  },
+  shapes::{point::Point, polygon::Polygon, polyline_set::PolylineSet, transform::Transform},
+  tolerance::Tolerance,
 };
 
 static INPUT_DATA: &str = include_str!("./data/input");
 static OUTPUT_DATA: &str = include_str!("./data/output");
 static OUTPUT_BOUNDARY_COUNT_DATA: &str = include_str!("./data/boundary_count");
 
+fn assert_transform_maps(transform: &Transform, input: &Polygon, placed: &Polygon) {
+  for (p, q) in input.vertices.iter().zip(placed.vertices.iter()) {
+    let p = transform.apply(*p);
+    assert_similar!(p.x, q.x);
+    assert_similar!(p.y, q.y);
+  }
+}
+
 #[test]
 fn test_official_data() {
   for TestCase {
@@ -21,11 +37,16 @@ fn test_official_data() {
     boundary_count,
   } in TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA)
   {
-    let (p1, p2, common_boundary_length) = polygon_matcher::best_match(&p1, &p2);
+    let result = polygon_matcher::best_match(&p1, &p2).unwrap();
+    let common_boundary_length = result.boundary;
 
-    let polyline_set = PolylineSet::from_segments(
-      &<Polygon as CommonBoundary<Vec<Segment>>>::common_boundary(&p1, &p2),
-    );
+    assert_transform_maps(&result.transform1, &p1, &result.polygon1);
+    assert_transform_maps(&result.transform2, &p2, &result.polygon2);
+
+    let segments = result
+      .polygon1
+      .common_segments_with(&result.polygon2, &result.tolerance);
+    let polyline_set = PolylineSet::from_segments(&segments, &result.tolerance);
 
     let common_boundaries_result = polyline_set.get_polylines().len();
 
@@ -49,3 +70,785 @@ fn test_official_data_read_io() {
     assert!(!p2.vertices.is_empty());
   }
 }
+
+fn polygon(points: &[(f64, f64)]) -> Polygon {
+  Polygon::new(points.iter().map(|&(x, y)| Point { x, y }).collect())
+}
+
+#[test]
+fn test_reflection() {
+  let notched = polygon(&[
+    (0_f64, 0_f64),
+    (4_f64, 0_f64),
+    (4_f64, 2_f64),
+    (3_f64, 2_f64),
+    (1_f64, 1_f64),
+    (1_f64, 2_f64),
+    (0_f64, 2_f64),
+  ]);
+  let mirrored_triangle = polygon(&[(-1_f64, 2_f64), (-3_f64, 2_f64), (-1_f64, 1_f64)]);
+  let notch_boundary = 1_f64 + 5_f64.sqrt();
+
+  let rigid = polygon_matcher::best_match(&mirrored_triangle, &notched).unwrap();
+  assert!(rigid.boundary < notch_boundary - 0.1);
+  assert!(!rigid.transform1.reflected);
+
+  let options = MatchOptions {
+    allow_reflection: true,
+    ..MatchOptions::default()
+  };
+  let flipped = polygon_matcher::best_match_with(&mirrored_triangle, &notched, &options).unwrap();
+  assert_similar!(flipped.boundary, notch_boundary);
+  assert!(flipped.transform1.reflected);
+  assert!(!flipped.transform2.reflected);
+
+  let mut vertices = mirrored_triangle.vertices.clone();
+  vertices.reverse();
+  assert_transform_maps(
+    &flipped.transform1,
+    &Polygon::new(vertices),
+    &flipped.polygon1,
+  );
+  assert_transform_maps(&flipped.transform2, &notched, &flipped.polygon2);
+}
+
+fn restricted(rotations: RotationSet) -> MatchOptions {
+  MatchOptions {
+    rotations,
+    ..MatchOptions::default()
+  }
+}
+
+#[test]
+fn test_fixed_rotation_translation() {
+  let notched = polygon(&[
+    (0_f64, 0_f64),
+    (4_f64, 0_f64),
+    (4_f64, 2_f64),
+    (3_f64, 2_f64),
+    (1_f64, 1_f64),
+    (1_f64, 2_f64),
+    (0_f64, 2_f64),
+  ]);
+  let triangle = polygon(&[(11_f64, 6_f64), (13_f64, 7_f64), (11_f64, 7_f64)]);
+
+  let result =
+    polygon_matcher::best_match_with(&triangle, &notched, &restricted(RotationSet::Fixed)).unwrap();
+
+  assert_similar!(result.boundary, 1_f64 + 5_f64.sqrt());
+  assert_similar!(result.transform1.rotation.sin(), 0_f64);
+  assert_similar!(result.transform1.translation.x, -10_f64);
+  assert_similar!(result.transform1.translation.y, -5_f64);
+  assert_eq!(result.transform2, Transform::default());
+  assert_transform_maps(&result.transform1, &triangle, &result.polygon1);
+  assert_transform_maps(&result.transform2, &notched, &result.polygon2);
+}
+
+#[test]
+fn test_restricted_rotations() {
+  let tall = polygon(&[
+    (0_f64, 0_f64),
+    (1_f64, 0_f64),
+    (1_f64, 2_f64),
+    (0_f64, 2_f64),
+  ]);
+  let wide = polygon(&[
+    (5_f64, 5_f64),
+    (8_f64, 5_f64),
+    (8_f64, 6_f64),
+    (5_f64, 6_f64),
+  ]);
+
+  for (rotations, boundary) in [
+    (RotationSet::Fixed, 1_f64),
+    (RotationSet::QuarterTurns, 2_f64),
+    (
+      RotationSet::Angles(vec![std::f64::consts::FRAC_PI_4]),
+      0_f64,
+    ),
+  ] {
+    let result = polygon_matcher::best_match_with(&tall, &wide, &restricted(rotations)).unwrap();
+
+    assert_similar!(result.boundary, boundary);
+    assert_transform_maps(&result.transform1, &tall, &result.polygon1);
+    assert_transform_maps(&result.transform2, &wide, &result.polygon2);
+  }
+}
+
+#[test]
+fn test_best_matches_skips_congruent_placements() {
+  let square = polygon(&[
+    (0_f64, 0_f64),
+    (1_f64, 0_f64),
+    (1_f64, 1_f64),
+    (0_f64, 1_f64),
+  ]);
+  let results =
+    polygon_matcher::best_matches_with(&square, &square, 5, &MatchOptions::default()).unwrap();
+
+  assert_eq!(results.len(), 1);
+  assert_similar!(results[0].boundary, 1_f64);
+}
+
+#[test]
+fn test_symmetric_placements() {
+  let tol = Tolerance::default();
+  let rectangle = polygon(&[
+    (0_f64, 0_f64),
+    (2_f64, 0_f64),
+    (2_f64, 1_f64),
+    (0_f64, 1_f64),
+  ]);
+  let square = polygon(&[
+    (0_f64, 0_f64),
+    (1_f64, 0_f64),
+    (1_f64, 1_f64),
+    (0_f64, 1_f64),
+  ]);
+  let result = polygon_matcher::best_match(&rectangle, &square).unwrap();
+  let placements = result.symmetric_placements(&rectangle, &square, &tol);
+
+  assert_eq!(placements.len(), 8);
+
+  for placement in placements {
+    assert_transform_maps(&placement.transform1, &rectangle, &placement.polygon1);
+    assert_transform_maps(&placement.transform2, &square, &placement.polygon2);
+    assert_similar!(
+      placement
+        .polygon1
+        .common_boundary_with(&placement.polygon2, &tol),
+      result.boundary
+    );
+  }
+}
+
+#[test]
+fn test_best_matches() {
+  let TestCase {
+    polygons: (p1, p2),
+    correct_answer,
+    ..
+  } = TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA).remove(3);
+
+  let results = polygon_matcher::best_matches_with(&p1, &p2, 4, &MatchOptions::default()).unwrap();
+
+  assert_eq!(results.len(), 4);
+  assert_similar!(results[0].boundary, correct_answer);
+
+  for (i, a) in results.iter().enumerate() {
+    assert_transform_maps(&a.transform1, &p1, &a.polygon1);
+    assert_transform_maps(&a.transform2, &p2, &a.polygon2);
+
+    for b in &results[i + 1..] {
+      assert!(a.boundary >= b.boundary);
+      assert!(!a.is_congruent(b));
+    }
+  }
+}
+
+#[test]
+fn test_best_matches_within_pair() {
+  let square = polygon(&[(0., 0.), (1., 0.), (1., 1.), (0., 1.)]);
+  let trapezoid = polygon(&[(0., 0.), (4., 0.), (3., 1.), (0., 1.)]);
+  let results =
+    polygon_matcher::best_matches_with(&square, &trapezoid, 5, &restricted(RotationSet::Fixed))
+      .unwrap();
+
+  // Without rotations only three pairs of edges can touch, each at several shifts.
+  assert_eq!(results.len(), 5);
+
+  for (i, a) in results.iter().enumerate() {
+    assert_similar!(a.boundary, 1.);
+    assert_transform_maps(&a.transform1, &square, &a.polygon1);
+    assert!(results[i + 1..].iter().all(|b| !a.is_congruent(b)));
+  }
+}
+
+#[test]
+fn test_pruning_keeps_best_boundary() {
+  let reflection = MatchOptions {
+    allow_reflection: true,
+    ..MatchOptions::default()
+  };
+
+  for TestCase {
+    polygons: (p1, p2), ..
+  } in TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA)
+    .into_iter()
+    .take(20)
+  {
+    for options in [&reflection, &restricted(RotationSet::QuarterTurns)] {
+      let pruned = polygon_matcher::best_match_with(&p1, &p2, options).unwrap();
+      let exhaustive = polygon_matcher::best_matches_with(&p1, &p2, 1, options).unwrap();
+      assert_similar!(pruned.boundary, exhaustive[0].boundary);
+    }
+  }
+}
+
+#[test]
+fn test_prepared_library() {
+  let library: Vec<Polygon> =
+    TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA)
+      .into_iter()
+      .take(6)
+      .flat_map(|case| [case.polygons.0, case.polygons.1])
+      .collect();
+  let prepared: Vec<PreparedPolygon> = library
+    .iter()
+    .map(|p| PreparedPolygon::new(p).unwrap())
+    .collect();
+  let options = MatchOptions {
+    allow_reflection: true,
+    ..MatchOptions::default()
+  };
+
+  for (p1, prepared1) in library.iter().zip(&prepared) {
+    for (p2, prepared2) in library.iter().zip(&prepared) {
+      let result = polygon_matcher::best_match_prepared(prepared1, prepared2, &options).unwrap();
+      let expected = polygon_matcher::best_match_with(p1, p2, &options).unwrap();
+
+      assert_similar!(result.boundary, expected.boundary);
+    }
+  }
+}
+
+fn shifted(polygon: &Polygon, x: f64) -> Polygon {
+  Polygon::new(
+    polygon
+      .vertices
+      .iter()
+      .map(|p| Point { x: p.x + x, y: p.y })
+      .collect(),
+  )
+}
+
+#[test]
+fn test_overlap_sweep_matches_scan() {
+  let tol = Tolerance::default();
+
+  for TestCase {
+    polygons: (p1, p2), ..
+  } in TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA)
+    .into_iter()
+    .take(30)
+  {
+    let width = p1.vertices.iter().map(|p| p.x.abs()).fold(0_f64, f64::max);
+
+    for (sliding, fixed) in p1.rotations().iter().zip(p2.rotations().iter()).take(4) {
+      let sliding = sliding.negate();
+
+      for k in -40..=40 {
+        let placed = shifted(&sliding, width * f64::from(k) / 20_f64);
+        assert_eq!(
+          placed.overlaps_with(fixed, &tol),
+          placed.intersects_with(fixed, &mut (0, 0), &tol)
+        );
+      }
+    }
+  }
+}
+
+fn scanned_boundary(polygon1: &Polygon, polygon2: &Polygon, tol: &Tolerance) -> f64 {
+  polygon1
+    .edges()
+    .flat_map(|e1| {
+      polygon2
+        .edges()
+        .map(move |e2| e1.common_boundary_with(&e2, tol))
+        .map(|s| s.map_or(0_f64, |s| s.length()))
+    })
+    .sum()
+}
+
+#[test]
+fn test_common_boundary_matches_scan() {
+  let tol = Tolerance::default();
+
+  for TestCase {
+    polygons: (p1, p2), ..
+  } in TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA)
+    .into_iter()
+    .take(20)
+  {
+    let result = polygon_matcher::best_match(&p1, &p2).unwrap();
+    let (placed1, placed2) = (&result.polygon1, &result.polygon2);
+
+    assert_eq!(
+      placed1.common_boundary_with(placed2, &tol),
+      scanned_boundary(placed1, placed2, &tol)
+    );
+    assert_eq!(
+      placed1.common_segments_with(placed2, &tol).len(),
+      placed1
+        .edges()
+        .flat_map(|e1| placed2
+          .edges()
+          .filter_map(move |e2| e1.common_boundary_with(&e2, &tol)))
+        .filter(|s| s.length() > tol.linear)
+        .count()
+    );
+  }
+}
+
+fn scaled(polygon: &Polygon, factor: f64) -> Polygon {
+  Polygon::new(
+    polygon
+      .vertices
+      .iter()
+      .map(|p| Point {
+        x: p.x * factor,
+        y: p.y * factor,
+      })
+      .collect(),
+  )
+}
+
+#[test]
+fn test_scale_invariance() {
+  for TestCase {
+    polygons: (p1, p2),
+    correct_answer,
+    ..
+  } in TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA)
+    .into_iter()
+    .take(15)
+  {
+    for factor in [1e-4, 1e4] {
+      let result = polygon_matcher::best_match(&scaled(&p1, factor), &scaled(&p2, factor)).unwrap();
+      assert_similar!(correct_answer, result.boundary / factor);
+    }
+  }
+}
+
+#[test]
+fn test_close_shifts_are_evaluated() {
+  let options = MatchOptions {
+    tolerance: Some(Tolerance::default()),
+    ..MatchOptions::default()
+  };
+
+  let square = polygon(&[(0_f64, 0_f64), (0.05, 0_f64), (0.05, 0.05), (0_f64, 0.05)]);
+  let result = polygon_matcher::best_match_with(&square, &square, &options).unwrap();
+  assert_similar!(result.boundary, 0.05);
+
+  let wide = polygon(&[(0_f64, 0_f64), (0.05, 0_f64), (0.05, 0.02), (0_f64, 0.02)]);
+  let narrow = polygon(&[(0_f64, 0_f64), (0.03, 0_f64), (0.03, 0.02), (0_f64, 0.02)]);
+  let result = polygon_matcher::best_match_with(&wide, &narrow, &options).unwrap();
+  assert_similar!(result.boundary, 0.03);
+}
+
+#[test]
+fn test_exact_predicates() {
+  let options = MatchOptions {
+    exact_predicates: true,
+    ..MatchOptions::default()
+  };
+
+  for TestCase {
+    polygons: (p1, p2),
+    correct_answer,
+    ..
+  } in TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA)
+    .into_iter()
+    .take(20)
+  {
+    let result = polygon_matcher::best_match_with(&p1, &p2, &options).unwrap();
+    assert_similar!(correct_answer, result.boundary);
+  }
+}
+
+#[test]
+fn test_exact_arithmetic() {
+  for TestCase {
+    polygons: (p1, p2),
+    correct_answer,
+    ..
+  } in TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA).into_iter()
+  {
+    let result = best_match_exact(&p1, &p2, &MatchOptions::default())
+      .unwrap()
+      .unwrap();
+
+    assert_similar!(correct_answer, result.boundary);
+    assert_transform_maps(&result.transform1, &p1, &result.polygon1);
+    assert_transform_maps(&result.transform2, &p2, &result.polygon2);
+  }
+}
+
+#[test]
+fn test_exact_arithmetic_needs_integers() {
+  let square = polygon(&[
+    (0_f64, 0_f64),
+    (1_f64, 0_f64),
+    (1_f64, 1_f64),
+    (0_f64, 1_f64),
+  ]);
+  let shifted = polygon(&[(0.5, 0_f64), (1_f64, 0_f64), (1_f64, 1_f64), (0_f64, 1_f64)]);
+
+  assert!(best_match_exact(&square, &square, &MatchOptions::default())
+    .unwrap()
+    .is_some());
+  assert!(
+    best_match_exact(&square, &shifted, &MatchOptions::default())
+      .unwrap()
+      .is_none()
+  );
+  assert!(
+    best_match_exact(&square, &square, &restricted(RotationSet::Fixed))
+      .unwrap()
+      .is_none()
+  );
+}
+
+#[test]
+fn test_exact_arithmetic_with_reflection() {
+  // The S tetromino has a half-turn symmetry, so its mirrored rotations are numbered
+  // right after its first 4 edges, and only its mirror image fills the notch.
+  let s_tetromino = polygon(&[
+    (1_f64, 0_f64),
+    (3_f64, 0_f64),
+    (3_f64, 1_f64),
+    (2_f64, 1_f64),
+    (2_f64, 2_f64),
+    (0_f64, 2_f64),
+    (0_f64, 1_f64),
+    (1_f64, 1_f64),
+  ]);
+  let z_notched = polygon(&[
+    (0_f64, 0_f64),
+    (5_f64, 0_f64),
+    (5_f64, 4_f64),
+    (4_f64, 4_f64),
+    (4_f64, 3_f64),
+    (3_f64, 3_f64),
+    (3_f64, 2_f64),
+    (1_f64, 2_f64),
+    (1_f64, 3_f64),
+    (2_f64, 3_f64),
+    (2_f64, 4_f64),
+    (0_f64, 4_f64),
+  ]);
+  let options = MatchOptions {
+    allow_reflection: true,
+    ..MatchOptions::default()
+  };
+
+  let float = polygon_matcher::best_match_with(&s_tetromino, &z_notched, &options).unwrap();
+  let exact = best_match_exact(&s_tetromino, &z_notched, &options)
+    .unwrap()
+    .unwrap();
+
+  assert_similar!(float.boundary, 8_f64);
+  assert_similar!(exact.boundary, 8_f64);
+  assert!(exact.transform1.reflected);
+
+  let mut vertices = s_tetromino.vertices.clone();
+  vertices.reverse();
+  assert_transform_maps(&exact.transform1, &Polygon::new(vertices), &exact.polygon1);
+}
+
+#[test]
+fn test_approximate_contacts() {
+  let approximate = MatchOptions {
+    approximate: true,
+    ..MatchOptions::default()
+  };
+  let mut exact = 0;
+
+  for TestCase {
+    polygons: (p1, p2),
+    correct_answer,
+    ..
+  } in TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA)
+    .into_iter()
+    .take(20)
+  {
+    let result = polygon_matcher::best_match_with(&p1, &p2, &approximate).unwrap();
+
+    assert!(result.boundary <= correct_answer + 1e-6);
+    assert_transform_maps(&result.transform1, &p1, &result.polygon1);
+    assert_transform_maps(&result.transform2, &p2, &result.polygon2);
+    assert_similar!(
+      result
+        .polygon1
+        .common_boundary_with(&result.polygon2, &Tolerance::default()),
+      result.boundary
+    );
+
+    if (result.boundary - correct_answer).abs() < 1e-6 {
+      exact += 1;
+    }
+  }
+
+  assert!(exact >= 15);
+}
+
+/// Bits of every vertex and of the boundary, to compare placements exactly.
+fn placement_bits(result: &polygon_matcher::MatchResult) -> Vec<u64> {
+  result
+    .polygon1
+    .vertices
+    .iter()
+    .chain(&result.polygon2.vertices)
+    .flat_map(|p| [p.x.to_bits(), p.y.to_bits()])
+    .chain([result.boundary.to_bits()])
+    .collect()
+}
+
+#[test]
+fn test_ties_do_not_depend_on_threads() {
+  let grid = polygon(&[
+    (0_f64, 0_f64),
+    (3_f64, 0_f64),
+    (3_f64, 1_f64),
+    (2_f64, 1_f64),
+    (2_f64, 2_f64),
+    (1_f64, 2_f64),
+    (1_f64, 1_f64),
+    (0_f64, 1_f64),
+  ]);
+  let square = polygon(&[
+    (0_f64, 0_f64),
+    (1_f64, 0_f64),
+    (1_f64, 1_f64),
+    (0_f64, 1_f64),
+  ]);
+  let pools = [1, 4].map(|threads| {
+    rayon::ThreadPoolBuilder::new()
+      .num_threads(threads)
+      .build()
+      .unwrap()
+  });
+
+  for _ in 0..20 {
+    let [single, parallel] = pools
+      .each_ref()
+      .map(|pool| pool.install(|| polygon_matcher::best_match(&square, &grid).unwrap()));
+    assert_eq!(placement_bits(&single), placement_bits(&parallel));
+  }
+}
+
+#[test]
+fn test_parallelism() {
+  let pool = rayon::ThreadPoolBuilder::new()
+    .num_threads(2)
+    .build()
+    .unwrap();
+  let modes = [
+    Parallelism::sequential().unwrap(),
+    Parallelism::Pool(std::sync::Arc::new(pool)),
+  ];
+
+  for TestCase {
+    polygons: (p1, p2), ..
+  } in TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA)
+    .into_iter()
+    .take(10)
+  {
+    let global = polygon_matcher::best_match(&p1, &p2).unwrap();
+
+    for parallelism in &modes {
+      let options = MatchOptions {
+        parallelism: parallelism.clone(),
+        ..MatchOptions::default()
+      };
+      let result = polygon_matcher::best_match_with(&p1, &p2, &options).unwrap();
+      assert_eq!(placement_bits(&result), placement_bits(&global));
+    }
+  }
+}
+
+#[test]
+fn test_budget() {
+  let cases = TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA);
+  let options = MatchOptions::default();
+
+  for TestCase {
+    polygons: (p1, p2), ..
+  } in cases.into_iter().take(10)
+  {
+    let full =
+      polygon_matcher::best_match_with_deadline(&p1, &p2, &options, &Budget::default()).unwrap();
+    assert!(full.exhaustive);
+    assert_eq!(
+      placement_bits(&full.result),
+      placement_bits(&polygon_matcher::best_match(&p1, &p2).unwrap())
+    );
+
+    let budgets = [
+      Budget {
+        pairs: Some(0),
+        ..Budget::default()
+      },
+      Budget::timeout(std::time::Duration::ZERO),
+    ];
+
+    for budget in &budgets {
+      let spent = polygon_matcher::best_match_with_deadline(&p1, &p2, &options, budget).unwrap();
+      assert!(!spent.exhaustive);
+      assert!(spent.result.boundary <= full.result.boundary + Tolerance::default().linear);
+      assert_transform_maps(&spent.result.transform1, &p1, &spent.result.polygon1);
+      assert_transform_maps(&spent.result.transform2, &p2, &spent.result.polygon2);
+      assert_similar!(
+        spent.result.boundary,
+        spent
+          .result
+          .polygon1
+          .common_boundary_with(&spent.result.polygon2, &Tolerance::default())
+      );
+    }
+  }
+}
+
+/// Star with `n` vertices on integer coordinates, its radii varying with `seed`.
+fn star(n: usize, seed: usize) -> Polygon {
+  Polygon::new(
+    (0..n)
+      .map(|i| {
+        let angle = std::f64::consts::TAU * i as f64 / n as f64;
+        let radius = if i % 2 == 0 {
+          100. + ((7 * i + seed) % 13) as f64
+        } else {
+          60. + ((11 * i + seed) % 7) as f64
+        };
+        Point {
+          x: (radius * angle.cos()).round(),
+          y: (radius * angle.sin()).round(),
+        }
+      })
+      .collect(),
+  )
+}
+
+#[test]
+fn test_timeout_on_large_polygons() {
+  let (p1, p2) = (star(400, 1), star(400, 5));
+  let start = std::time::Instant::now();
+  let budget = Budget::timeout(std::time::Duration::from_millis(10));
+  let spent =
+    polygon_matcher::best_match_with_deadline(&p1, &p2, &MatchOptions::default(), &budget).unwrap();
+
+  assert!(!spent.exhaustive);
+  assert!(start.elapsed() < std::time::Duration::from_secs(1));
+  assert_transform_maps(&spent.result.transform1, &p1, &spent.result.polygon1);
+}
+
+#[test]
+fn test_budget_without_rotations() {
+  let (p1, p2) = (
+    polygon(&[(0., 0.), (1., 0.), (1., 1.), (0., 1.)]),
+    polygon(&[(0., 0.), (2., 0.), (2., 1.), (0., 1.)]),
+  );
+  let budget = Budget {
+    pairs: Some(0),
+    ..Budget::default()
+  };
+  let spent =
+    polygon_matcher::best_match_with_deadline(&p1, &p2, &restricted(RotationSet::Fixed), &budget)
+      .unwrap();
+
+  assert!(!spent.exhaustive);
+  assert_similar!(spent.result.boundary, 0.);
+  assert_transform_maps(&spent.result.transform1, &p1, &spent.result.polygon1);
+  assert_similar!(
+    spent
+      .result
+      .polygon1
+      .common_boundary_with(&spent.result.polygon2, &Tolerance::default()),
+    0.
+  );
+}
+
+#[test]
+fn test_progress() {
+  let TestCase {
+    polygons: (p1, p2), ..
+  } = TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA).remove(0);
+  let mut reports = vec![];
+
+  let result = polygon_matcher::best_match_cancellable(
+    &p1,
+    &p2,
+    &MatchOptions::default(),
+    &CancelToken::new(),
+    |progress| reports.push(progress),
+  )
+  .unwrap();
+
+  let last = reports.last().unwrap();
+  assert!(reports.len() > 1);
+  assert!(reports
+    .windows(2)
+    .all(|w| w[0].evaluated < w[1].evaluated && w[0].best <= w[1].best));
+  assert_eq!(last.evaluated, last.total);
+  assert_similar!(last.best, result.boundary);
+  assert_eq!(
+    placement_bits(&result),
+    placement_bits(&polygon_matcher::best_match(&p1, &p2).unwrap())
+  );
+}
+
+#[test]
+fn test_cancel() {
+  let TestCase {
+    polygons: (p1, p2), ..
+  } = TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA).remove(0);
+  let options = MatchOptions::default();
+
+  let cancelled = CancelToken::new();
+  cancelled.cancel();
+  let result = polygon_matcher::best_match_cancellable(&p1, &p2, &options, &cancelled, |_| {});
+  assert!(matches!(result, Err(PuzzleError::Cancelled)));
+
+  let cancel = CancelToken::new();
+  let mut reports = 0;
+  let result = polygon_matcher::best_match_cancellable(&p1, &p2, &options, &cancel, |_| {
+    reports += 1;
+    cancel.cancel();
+  });
+  assert!(matches!(result, Err(PuzzleError::Cancelled)));
+  assert_eq!(reports, 1);
+}
+
+#[test]
+fn test_stats() {
+  for TestCase {
+    polygons: (p1, p2), ..
+  } in TestCase::vec_from(INPUT_DATA, OUTPUT_DATA, OUTPUT_BOUNDARY_COUNT_DATA)
+    .into_iter()
+    .take(10)
+  {
+    let stats = polygon_matcher::best_match(&p1, &p2).unwrap().stats;
+    let all =
+      polygon_matcher::best_matches_with(&p1, &p2, 1, &MatchOptions::default()).unwrap()[0].stats;
+
+    assert!(stats.pairs > 0);
+    assert!(stats.pairs <= all.pairs);
+    assert_eq!(stats.overlap_tests, stats.shifts - stats.skipped_shifts);
+    assert_eq!(stats.overlap_tests, stats.overlaps + stats.boundaries);
+    assert!(all.pairs <= (p1.len() * p2.len()) as u64);
+  }
+}
+
+#[test]
+fn test_degenerate_polygons() {
+  let square = polygon(&[(0., 0.), (1., 0.), (1., 1.), (0., 1.)]);
+  let segment = polygon(&[(0., 0.), (1., 0.)]);
+  let infinite = polygon(&[(0., 0.), (f64::INFINITY, 0.), (0., 1.)]);
+
+  for degenerate in [&segment, &infinite, &polygon(&[])] {
+    assert!(matches!(
+      polygon_matcher::best_match(degenerate, &square),
+      Err(PuzzleError::DegeneratePolygon(_))
+    ));
+    assert!(matches!(
+      polygon_matcher::best_matches_with(&square, degenerate, 2, &MatchOptions::default()),
+      Err(PuzzleError::DegeneratePolygon(_))
+    ));
+  }
+
+  assert!(matches!(
+    polygon_matcher::best_match_with(&square, &square, &restricted(RotationSet::Angles(vec![]))),
+    Err(PuzzleError::InvalidOptions(_))
+  ));
+}
@@ -26,7 +26,7 @@ fn zip_all(
   let odd = polygons.iter().step_by(2).cloned();
   let even = polygons.iter().skip(1).step_by(2).cloned();
   let polygon_pair = odd.zip(even);
-  let result = answers.into_iter().zip(boundary_counts.into_iter());
+  let result = answers.into_iter().zip(boundary_counts);
   polygon_pair
     .zip(result)
     .map(|(polygons, (correct_answer, boundary_count))| TestCase {